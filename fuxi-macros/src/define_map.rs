@@ -5,6 +5,7 @@ use syn::{Ident, Result, Token, Type, Visibility, parse::ParseStream};
 mod kw {
     syn::custom_keyword!(is);
     syn::custom_keyword!(to);
+    syn::custom_keyword!(serde);
 }
 
 pub fn generate(ast: ParseStream) -> Result<TokenStream> {
@@ -18,6 +19,13 @@ pub fn generate(ast: ParseStream) -> Result<TokenStream> {
     let key = ast.parse::<Box<Type>>()?;
     ast.parse::<kw::to>()?;
     let value = ast.parse::<Box<Type>>()?;
+    let serde = if ast.peek(Token![,]) {
+        ast.parse::<Token![,]>()?;
+        ast.parse::<kw::serde>()?;
+        true
+    } else {
+        false
+    };
 
     let keys_iter_name = Ident::new(&format!("{name}KeysIterable"), name.span());
     let values_iter_name = Ident::new(&format!("{name}ValuesIterable"), name.span());
@@ -99,6 +107,24 @@ pub fn generate(ast: ParseStream) -> Result<TokenStream> {
         }
     });
 
+    if serde {
+        tokens.push(quote! {
+            impl ::serde::Serialize for #name {
+                #[inline]
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&*self.0.read(), serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                #[inline]
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <crate::types::alias::Map<#key, #value> as ::serde::Deserialize>::deserialize(deserializer).map(Self::from)
+                }
+            }
+        });
+    }
+
     tokens.push(quote! {
         #[::pyo3::pyclass(frozen)]
         #[derive(Clone, Default)]
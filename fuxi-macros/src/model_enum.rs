@@ -18,6 +18,8 @@ pub fn generate(attr: TokenStream, ast: ItemEnum) -> Result<TokenStream> {
         return Err(syn::Error::new_spanned(ident, "不支持抽象枚举类型"));
     } else if let Some((ident, _)) = options.ext {
         return Err(syn::Error::new_spanned(ident, "不支持扩展枚举类型"));
+    } else if let Some(ident) = options.serde {
+        return Err(syn::Error::new_spanned(ident, "枚举类型默认支持序列化"));
     }
 
     let name = &ast.ident;
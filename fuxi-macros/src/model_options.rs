@@ -8,12 +8,14 @@ mod kw {
     syn::custom_keyword!(python);
     syn::custom_keyword!(abs);
     syn::custom_keyword!(ext);
+    syn::custom_keyword!(serde);
 }
 
 pub enum OptionType {
     Python(Ident),
     Abs(Ident),
     Ext(Ident, Ident),
+    Serde(Ident),
 }
 
 impl Parse for OptionType {
@@ -29,6 +31,9 @@ impl Parse for OptionType {
             input.parse::<Token![=]>()?;
             let ext = input.parse::<Ident>()?;
             Ok(Self::Ext(ident, ext))
+        } else if input.peek(kw::serde) {
+            let ident = input.parse::<Ident>()?;
+            Ok(Self::Serde(ident))
         } else {
             Err(input.error("无效属性"))
         }
@@ -40,6 +45,7 @@ pub struct Options {
     pub python: Option<Ident>,
     pub abs: Option<Ident>,
     pub ext: Option<(Ident, Ident)>,
+    pub serde: Option<Ident>,
 }
 
 impl Parse for Options {
@@ -65,6 +71,12 @@ impl Parse for Options {
                     }
                     options.ext = Some((ident, ext));
                 }
+                OptionType::Serde(ident) => {
+                    if options.serde.is_some() {
+                        return Err(Error::new_spanned(ident, "重复属性: serde"));
+                    }
+                    options.serde = Some(ident);
+                }
             }
         }
         Ok(options)
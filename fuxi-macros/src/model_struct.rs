@@ -5,7 +5,7 @@ use syn::{Fields, Ident, ItemStruct, Result, Visibility, spanned::Spanned};
 
 pub fn generate(attr: TokenStream, ast: ItemStruct) -> Result<TokenStream> {
     let options = syn::parse2::<Options>(attr)?;
-    let data_tokens = generate_data(&ast);
+    let data_tokens = generate_data(&options, &ast);
     let safe_tokens = generate_safe(&options, &ast);
     Ok(quote! {
         #data_tokens
@@ -13,7 +13,7 @@ pub fn generate(attr: TokenStream, ast: ItemStruct) -> Result<TokenStream> {
     })
 }
 
-fn generate_data(ast: &ItemStruct) -> TokenStream {
+fn generate_data(options: &Options, ast: &ItemStruct) -> TokenStream {
     let name = Ident::new(&format!("{}Data", ast.ident), ast.ident.span());
     let vis = &ast.vis;
     let attrs = &ast.attrs;
@@ -24,6 +24,12 @@ fn generate_data(ast: &ItemStruct) -> TokenStream {
         #(#attrs)*
     });
 
+    if options.serde.is_some() {
+        tokens.push(quote! {
+            #[derive(::serde::Serialize, ::serde::Deserialize)]
+        });
+    }
+
     let mut field_tokens: Vec<TokenStream> = vec![];
     match &ast.fields {
         Fields::Named(fields) => {
@@ -31,7 +37,9 @@ fn generate_data(ast: &ItemStruct) -> TokenStream {
                 let field_name = &field.ident;
                 let field_type = &field.ty;
                 let field_vis = &field.vis;
+                let field_attrs = &field.attrs;
                 field_tokens.push(quote! {
+                    #(#field_attrs)*
                     #field_vis #field_name: #field_type,
                 });
             }
@@ -40,7 +48,9 @@ fn generate_data(ast: &ItemStruct) -> TokenStream {
             for field in fields.unnamed.iter() {
                 let field_type = &field.ty;
                 let field_vis = &field.vis;
+                let field_attrs = &field.attrs;
                 field_tokens.push(quote! {
+                    #(#field_attrs)*
                     #field_vis #field_type,
                 });
             }
@@ -166,6 +176,24 @@ fn generate_safe(options: &Options, ast: &ItemStruct) -> TokenStream {
         }
     });
 
+    if options.serde.is_some() {
+        tokens.push(quote! {
+            impl ::serde::Serialize for #name {
+                #[inline]
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&*self.0.read(), serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                #[inline]
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <#data_name as ::serde::Deserialize>::deserialize(deserializer).map(Self::from)
+                }
+            }
+        });
+    }
+
     if options.python.is_some() {
         tokens.push(quote! {
             #[::pyo3::pymethods]
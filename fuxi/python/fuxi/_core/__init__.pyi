@@ -1,10 +1,12 @@
-from .base import LogLevel, Mode, Market, Method, Direction, Side, OrderStatus, Interval, Timer, Volume, Pnl
+from .base import LogLevel, Mode, Market, Method, Direction, Side, OrderStatus, Interval, Timer, GapPolicy, Resample, DataSource, DataFormat, Volume, Pnl
 from .code import Coins, Codes
-from .market import Candle, FundingRate, Symbol
+from .market import Candle, FundingRate, OrderBook, Trade, Symbol
 from .order import Order
 from .position import SidePosition, Position
 from .context import Context
 from .backtest import Backtest
+from .data_store import DataStore
+from .results import ResultStore, Run
 
 __all__ = [
     "LogLevel",
@@ -16,16 +18,25 @@ __all__ = [
     "OrderStatus",
     "Interval",
     "Timer",
+    "GapPolicy",
+    "Resample",
+    "DataSource",
+    "DataFormat",
     "Volume",
     "Pnl",
     "Coins",
     "Codes",
     "Candle",
     "FundingRate",
+    "OrderBook",
+    "Trade",
     "Symbol",
     "Order",
     "SidePosition",
     "Position",
     "Context",
     "Backtest",
+    "DataStore",
+    "ResultStore",
+    "Run",
 ]
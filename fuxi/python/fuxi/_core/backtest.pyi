@@ -1,6 +1,9 @@
-from typing import Any, List, Tuple
+from typing import Any, Callable, Dict, List, Optional, Tuple
+from polars import DataFrame
 from .code import Codes
+from .base import GapPolicy, DataSource, DataFormat, Resample
 from .alias import Size, Time
+from .context import Context
 
 class Backtest:
     """回测引擎"""
@@ -13,6 +16,14 @@ class Backtest:
     """历史数据大小"""
     offset: int
     """数据偏移量"""
+    context: Context
+    """上下文, 单步调试时在两步之间查看资金, 持仓和订单"""
+    finished: bool
+    """是否已回放到结束时间"""
+    seed: int
+    """随机数种子"""
+    run_id: Optional[str]
+    """运行ID, 保存回测结果后设置"""
 
     def __init__(
         self,
@@ -24,19 +35,233 @@ class Backtest:
         swap: Size = 1000,
         history_size: int = 5000,
         force_sync_data: bool = False,
+        gap_policy: GapPolicy = GapPolicy.Fill,
+        data_dir: Optional[str] = None,
+        data_format: DataFormat = DataFormat.Ipc,
+        data_source: DataSource = DataSource.Github,
+        data_source_path: Optional[str] = None,
+        download_concurrency: int = 4,
+        download_retries: int = 3,
+        download_timeout: int = 60,
+        skip_failed_data: bool = False,
+        tz: str = "Asia/Shanghai",
+        book_fills: bool = False,
+        tick_replay: bool = False,
+        skip_idle: bool = False,
+        checkpoint: Optional[str] = None,
+        checkpoint_every: str = "1d",
+        debug: bool = False,
+        seed: Optional[int] = None,
+        benchmark: Optional[List[Codes]] = None,
+        results_dir: Optional[str] = None,
+        save_results: bool = True,
     ):
         """
         初始化回测引擎
         - [`strategy`]: 策略实例
-        - [`begin`]: 开始时间
-        - [`end`]: 结束时间
+        - [`begin`]: 开始时间, 支持的格式见`Context.str_to_time`, 如2024-01-01, 2024-01-01T00:00:00Z, now-30d
+        - [`end`]: 结束时间, 同上
         - [`symbols`]: 交易对配置
         - [`symbols.item`]: (交易对, 吃单费率, 挂单费率, 杠杆倍数)
         - [`spot`]: 现货资金
         - [`swap`]: 合约资金
         - [`history_size`]: 历史数据大小
-        - [`force_sync_data`]: 强制同步历史行情数据
+        - [`force_sync_data`]: 强制全量同步历史行情数据, 默认只增量同步本地缺少的新数据
+        - [`gap_policy`]: 缺失数据处理方式
+        - [`data_dir`]: 历史数据目录, 默认读取环境变量`FUXI_DATA_DIR`, 否则为当前目录下的`data`
+        - [`data_format`]: 历史数据写入格式
+        - [`data_source`]: 历史数据源
+        - [`data_source_path`]: 数据源路径, 本地数据源必填, 可以是数据目录或zip压缩包
+        - [`download_concurrency`]: 最大并发下载数
+        - [`download_retries`]: 下载超时、连接失败或服务端错误时的重试次数, 指数退避
+        - [`download_timeout`]: 单次请求超时时间(秒)
+        - [`skip_failed_data`]: 下载失败时跳过该交易对继续回测, 否则终止回测
+        - [`tz`]: 时区(IANA名称, 如UTC), 用于时间解析, 日/小时边界和K线时间
+        - [`book_fills`]: 使用盘口快照撮合, 限价单按排队位置和盘口深度成交, 没有盘口数据的交易对使用K线撮合
+        - [`tick_replay`]: 逐笔成交回放, 上下文时间随每笔成交推进, 订单按实际成交撮合并触发on_trade, 没有逐笔数据的交易对仍按1分钟K线回放
+        - [`skip_idle`]: 跳过空闲分钟的on_signal回调, 只在有逐笔成交, 订单成交或定时器触发时回调; 没有事件的分钟只在引擎内记录权益和推进盘口, 不进入Python, 其间完结的K线在下一个有事件的分钟一起追加; 订阅定时器时需要先移除内置的Minutely定时器, 否则报错
+        - [`checkpoint`]: 检查点文件路径(json), 回放时定期保存引擎状态, 异常中断后可以用`load_checkpoint`恢复
+        - [`checkpoint_every`]: 检查点保存间隔(回测时间), 数字+单位(s/m/h/d/w)
+        - [`debug`]: 单步调试, `launche`只加载数据不回放, 之后用`step`/`step_until`逐步推进
+        - [`seed`]: 随机数种子, 引擎内所有随机组件共用一个按种子初始化的随机数生成器, 默认随机生成并记录在`seed`和`config()`中; 相同的种子和数据得到完全相同的订单和权益
+        - [`benchmark`]: 基准交易对, 需要包含在[`symbols`]中; 以期初权益在回测开始时等权买入并持有, 单个交易对即买入持有基准
+        - [`results_dir`]: 回测结果目录, 默认读取环境变量`FUXI_RESULTS_DIR`, 否则为当前目录下的`results`
+        - [`save_results`]: 回测完成后保存结果并记录日志, 见`save_run`
+
+        只回调策略重写过的事件方法, 没有重写on_candle/on_signal等方法时不会进入Python
+        """
+
+    def equity(self) -> DataFrame:
+        """
+        权益曲线, 每分钟按收盘价计算持仓浮动盈亏
+        - 返回列: time, equity, benchmark(设置基准时)
+        """
+
+    def config(self) -> str:
+        """回测配置(json): 时间区间, 时区, 交易对, 初始资金, 数据和撮合选项, 随机数种子"""
+
+    def metrics(self) -> DataFrame:
+        """
+        回测指标, 一行
+        - seed: 随机数种子
+        - final_equity: 最终权益
+        - total_return: 总收益率
+        - annual_return: 年化收益率
+        - volatility: 年化波动率(日收益)
+        - sharpe: 夏普比率(日收益, 无风险利率为0)
+        - max_drawdown: 最大回撤
+        - orders: 订单数
+        - fills: 有成交的订单数
+        - fees: 手续费
+        - 设置基准时(按日收益计算):
+            - benchmark_return: 基准总收益率
+            - alpha: 年化Alpha
+            - beta: Beta
+            - correlation: 与基准日收益的相关系数
+            - tracking_error: 年化跟踪误差
+            - information_ratio: 信息比率
+        """
+
+    def monte_carlo(
+        self,
+        method: Resample = Resample.Shuffle,
+        runs: int = 1000,
+        block: str = "1d",
+        fee_jitter: float = 0.5,
+        slippage: float = 0.0005,
+        confidence: float = 0.95,
+        seed: Optional[int] = None,
+    ) -> Tuple[DataFrame, DataFrame]:
+        """
+        蒙特卡洛稳健性分析, 对已完成回测的权益曲线重采样
+        - [`method`]: 重采样方式
+            - Shuffle: 按成交把权益曲线切分为交易段并打乱顺序
+            - Bootstrap: 按[`block`]长度分块有放回抽取每分钟收益率
+            - Perturb: 每笔成交的手续费按[1-fee_jitter, 1+fee_jitter]倍随机缩放, 并加收[0, slippage]比例成交额的随机滑点
+        - [`runs`]: 模拟次数
+        - [`block`]: 自助法的块长度, 数字+单位(s/m/h/d/w)
+        - [`fee_jitter`]: 手续费扰动比例, 0~1
+        - [`slippage`]: 最大滑点比例
+        - [`confidence`]: 置信度, 如0.95对应2.5%~97.5%分位数
+        - [`seed`]: 随机数种子, 相同种子结果相同, 默认从引擎的随机数生成器获取
+        - 返回: (模拟结果, 汇总)
+            - 模拟结果: 每次模拟一行, run, final_equity, max_drawdown, sharpe
+            - 汇总: 每个指标一行, metric, actual(实际回测值), mean, std, lower, median, upper
+        """
+
+    def save_checkpoint(self, path: str):
+        """
+        保存检查点: 下一个待回放的分钟, 现货&合约资金, 持仓, 订单, 排队位置, 数据偏移量和权益曲线
+        - 策略自身的状态不会保存, 恢复后通过`get_candle`等按当前时间重新计算
+        """
+
+    def load_checkpoint(self, path: str):
+        """
+        从检查点恢复, 需要在`launche`前调用, 回放从检查点记录的待回放分钟继续
+        - 回测区间和历史数据大小需要与保存时一致, 交易对需要包含检查点中的交易对
+        - 可以用不同的策略参数从同一个检查点恢复, 比较不同的后续走势
+        """
+
+    def report(self, path: str):
+        """
+        生成单个静态HTML报告, 不引用外部资源, 相同的回测结果生成相同的文件
+        - 指标, 权益曲线, 回撤, 月度收益热力图
+        - 按交易对和备注归因的已实现/未实现盈亏, 手续费和资金费
+        - 成交列表
+        - 配置: 交易对, 费率, 杠杆倍数, 开始/结束时间, 历史数据大小, 随机数种子和引擎版本
+        """
+
+    def attribution(self, by: List[str] = ["code"]) -> DataFrame:
+        """
+        盈亏归因, 按维度分组汇总
+        - by: 分组维度, 可选code, direction, remark, 为空时汇总全部
+        - 列: 分组维度, realized(已实现盈亏), unrealized(期末浮动盈亏), fees(手续费), funding(资金费), net(净盈亏)
+        - 开仓成交按先进先出匹配平仓, 盈亏归到开仓订单的备注, 手续费归到产生手续费的订单的备注
+        - 回测未加载资金费率, 资金费为0
         """
 
     def launche(self):
-        """启动回测"""
+        """启动回测, 单步调试时只加载数据; 回测完成后按[`save_results`]保存结果"""
+
+    def save_run(self) -> str:
+        """
+        保存回测结果到[`results_dir`]下以运行ID命名的目录, 返回运行ID, 用`ResultStore`列出, 加载和比较
+        - run.json: 运行ID, 保存时间, 引擎版本, 时间区间, 交易对, 随机数种子
+        - config.json: 回测配置, 同`config()`
+        - metrics.ipc: 回测指标, 同`metrics()`
+        - equity.ipc: 权益曲线, 同`equity()`
+        - trades.ipc: 成交列表
+        - orders.json: 全部订单
+        - logs.jsonl: 输出的日志, 每行一个JSON字符串, 需要以save_results=True创建回测
+        """
+
+    def step(self, bars: int = 1) -> bool:
+        """
+        单步调试: 回放指定数量的有事件的分钟, 空闲分钟只在引擎内推进, 需要以debug=True创建回测
+        - 返回: 是否还有未回放的分钟
+        """
+
+    def step_until(
+        self,
+        time: Optional[str] = None,
+        order: bool = False,
+        predicate: Optional[Callable[["Backtest"], bool]] = None,
+    ) -> bool:
+        """
+        单步调试: 回放到满足任一条件的分钟为止, 需要以debug=True创建回测
+        - [`time`]: 到达指定时间, 支持的格式见`Context.str_to_time`
+        - [`order`]: 有订单成交
+        - [`predicate`]: 每回放一个有事件的分钟以回测引擎为参数调用, 返回真值时停止
+        - 返回: 是否还有未回放的分钟
+        """
+
+    @classmethod
+    def sweep(
+        cls,
+        strategy_factory: Callable[..., Any],
+        param_grid: Dict[str, List[Any]],
+        begin: str,
+        end: str,
+        symbols: List[Tuple[Codes, Size, Size, Size]],
+        **kwargs: Any,
+    ) -> DataFrame:
+        """
+        参数扫描, K线只加载一次并在多个回测之间共享, 回测并行执行
+        - [`strategy_factory`]: 策略工厂, 以参数作为关键字参数调用, 返回策略实例
+        - [`param_grid`]: 参数网格, 参数名 -> 候选值列表, 按笛卡尔积展开
+        - [`begin`]/[`end`]/[`symbols`]: 同`Backtest.__init__`
+        - [`kwargs`]: 其它`Backtest.__init__`参数, 不支持每个回测单独写文件的checkpoint, results_dir和save_results, 回测结果不保存
+        - 返回: 每组参数一行, 参数列 + seed + `metrics()`的指标列 + error(失败原因, 成功为空)
+
+        策略回调需要获取GIL, 并行主要加速数据处理和撮合, 纯Python计算密集的策略收益有限
+        """
+
+    @classmethod
+    def walk_forward(
+        cls,
+        strategy_factory: Callable[..., Any],
+        param_grid: Dict[str, List[Any]],
+        begin: str,
+        end: str,
+        symbols: List[Tuple[Codes, Size, Size, Size]],
+        in_sample: str = "90d",
+        out_sample: str = "30d",
+        anchored: bool = False,
+        metric: str = "sharpe",
+        maximize: bool = True,
+        **kwargs: Any,
+    ) -> Tuple[DataFrame, DataFrame]:
+        """
+        滚动优化(walk-forward), 每个窗口在样本内按`sweep`扫描参数, 用指标最优的参数回测紧随其后的样本外区间
+        - [`strategy_factory`]/[`param_grid`]: 同`sweep`
+        - [`begin`]/[`end`]/[`symbols`]: 同`Backtest.__init__`, 第一个样本外区间从begin+in_sample开始
+        - [`in_sample`]: 样本内区间长度, 数字+单位(s/m/h/d/w)
+        - [`out_sample`]: 样本外区间长度, 同时是窗口的滚动步长, 最后一个窗口截止到end
+        - [`anchored`]: 锚定窗口, 样本内区间始终从begin开始, 否则为固定长度的滚动窗口
+        - [`metric`]: 选择参数的指标, `metrics()`的列名
+        - [`maximize`]: 指标越大越好, 如max_drawdown应设为False
+        - [`kwargs`]: 其它`Backtest.__init__`参数, 不支持每个回测单独写文件的checkpoint, results_dir和save_results, 回测结果不保存
+        - 返回: (窗口, 权益曲线)
+            - 窗口: 每个窗口一行, window, is_begin, is_end, oos_begin, oos_end, 最优参数列, is_<metric>(样本内指标), seed(样本外回测的种子), 样本外的`metrics()`指标列
+            - 权益曲线: 拼接的样本外权益, 列为time, equity, window, 每个窗口按上一个窗口的期末权益缩放
+        """
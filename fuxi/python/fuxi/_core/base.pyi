@@ -125,7 +125,7 @@ class Interval(Enum):
     def members() -> List[Interval]: ...
 
 class Timer(Enum):
-    """定时器"""
+    """内置定时器, on_timer收到的名称为str(Timer.Daily)等"""
 
     Daily = auto()
     """每天"""
@@ -136,6 +136,54 @@ class Timer(Enum):
     @staticmethod
     def members() -> List[Timer]: ...
 
+class GapPolicy(Enum):
+    """缺失数据处理方式"""
+
+    Fill = auto()
+    """收盘价前向填充开高低收, 成交量为0"""
+    Null = auto()
+    """保留空值"""
+    Missing = auto()
+    """保留空值, 并标记为未完成(finished=false)"""
+    @staticmethod
+    def members() -> List[GapPolicy]: ...
+
+class Resample(Enum):
+    """回测结果重采样方式"""
+
+    Shuffle = auto()
+    """按成交切分权益曲线, 打乱交易顺序"""
+    Bootstrap = auto()
+    """分块自助法重采样每分钟收益率"""
+    Perturb = auto()
+    """随机扰动每笔成交的手续费和滑点"""
+    @staticmethod
+    def members() -> List[Resample]: ...
+
+class DataSource(Enum):
+    """历史数据源"""
+
+    Github = auto()
+    """Github上的freqtrade数据"""
+    Local = auto()
+    """本地目录(feather/arrow/ipc/parquet/csv)"""
+    Hyperliquid = auto()
+    """Hyperliquid candleSnapshot接口"""
+    Fixture = auto()
+    """离线模拟数据"""
+    @staticmethod
+    def members() -> List[DataSource]: ...
+
+class DataFormat(Enum):
+    """数据存储格式"""
+
+    Ipc = auto()
+    """Arrow IPC/feather"""
+    Parquet = auto()
+    """Parquet(zstd压缩, 带行组统计信息)"""
+    @staticmethod
+    def members() -> List[DataFormat]: ...
+
 class Volume:
     """数量"""
 
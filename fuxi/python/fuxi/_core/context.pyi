@@ -1,4 +1,5 @@
 from typing import Dict, Optional
+from datetime import tzinfo
 from .market import Symbol
 from .code import Codes
 from .base import LogLevel, Mode, Volume, Method, Direction, Side
@@ -10,6 +11,8 @@ class Context:
 
     mode: Mode
     """模式"""
+    tz: tzinfo
+    """时区"""
 
     time: Time
     """当前时间"""
@@ -48,6 +51,7 @@ class Context:
         - [`size`]: 订单数量
         - [`price`]: 订单价格
         - [`remark`]: 备注
+        - 开仓冻结保证金(数量*价格/杠杆, 市价单按当前时间已收盘的K线收盘价), 平仓冻结持仓, 不足时订单状态为Rejected
         """
 
     def cancel_order(self, code: Codes, id: str):
@@ -57,16 +61,40 @@ class Context:
         - [`id`]: 订单id
         """
 
-    @staticmethod
-    def millis_to_time(millis: int) -> Time:
+    def set_lever(self, code: Codes, lever: Size):
+        """
+        设置杠杆倍数, 有持仓或挂单时不能修改
+        - [`code`]: 交易对
+        - [`lever`]: 杠杆倍数, 整数, 不超过交易对的最大杠杆
+        """
+
+    def add_timer(
+        self,
+        name: str,
+        every: Optional[str] = None,
+        cron: Optional[str] = None,
+        offset: Optional[str] = None,
+        tz: Optional[str] = None,
+    ):
+        """
+        添加定时器, 触发时以名称回调on_timer
+        - [`name`]: 定时器名称, 不能与已有定时器重复(内置: Daily/Hourly/Minutely)
+        - [`every`]: 固定周期, 数字+单位(s/m/h/d/w), 如5m, 4h, 1w(周一00:00对齐), 周期必须大于0
+        - [`cron`]: cron表达式(分 时 日 月 周), 如0 0 * * 1
+        - [`offset`]: 触发偏移, 格式同every
+        - [`tz`]: 对齐时区, 默认为上下文时区
+        """
+
+    def remove_timer(self, name: str) -> bool:
+        """移除定时器, 返回是否存在"""
+
+    def millis_to_time(self, millis: int) -> Time:
         """毫秒转换为时间"""
 
-    @staticmethod
-    def nanos_to_time(nanos: int) -> Time:
+    def nanos_to_time(self, nanos: int) -> Time:
         """纳秒转换为时间"""
 
-    @staticmethod
-    def str_to_time(s: int) -> Time:
+    def str_to_time(self, s: str) -> Time:
         """
         字符串转换为时间
 
@@ -85,6 +113,13 @@ class Context:
         - 202001020304
         - 20200102030405
         - 20200102030405678
+        - 2020-01-02T03:04:05 (ISO-8601, 与上面的格式一样按所在时区处理)
+        - 2020-01-02T03:04:05Z / 2020-01-02T03:04:05+08:00 (RFC3339, 带时区偏移)
+        - 1577934245 (秒时间戳, 10位且不是合法的紧凑格式时)
+        - @1577934245 (秒时间戳)
+        - 1577934245678 (毫秒时间戳, 13位)
+        - now (当前时间)
+        - 相对时间: 在以上任意格式后追加[+-]数字+单位(s/m/h/d/w), 可以叠加, 如now-30d, 2024-01-01+90d, now-1d+8h
         """
 
     @staticmethod
@@ -0,0 +1,135 @@
+from typing import Optional
+from datetime import tzinfo
+from polars import DataFrame
+from .code import Codes
+from .base import Market, Interval, DataFormat
+
+class DataStore:
+    """本地历史数据"""
+
+    dir: str
+    """数据目录"""
+    format: DataFormat
+    """写入格式, 读取时按扩展名识别"""
+    tz: tzinfo
+    """时区"""
+
+    def __init__(
+        self,
+        dir: Optional[str] = None,
+        format: DataFormat = DataFormat.Ipc,
+        tz: Optional[str] = None,
+    ):
+        """
+        初始化本地历史数据
+        - [`dir`]: 数据目录, 默认读取环境变量`FUXI_DATA_DIR`, 否则为当前目录下的`data`
+        - [`format`]: 写入格式
+        - [`tz`]: 时区, 默认为Asia/Shanghai
+        """
+
+    def import_file(self, code: Codes, path: str, interval: Interval = Interval.Min) -> int:
+        """
+        导入K线文件, 按扩展名识别格式(csv/parquet/feather/arrow/ipc), 返回导入行数
+        - [`code`]: 交易对
+        - [`path`]: 文件路径
+        - [`interval`]: 周期
+
+        CSV格式(带表头):
+        - time: 时间, 毫秒时间戳或ISO-8601(无时区按UTC处理), 也可命名为date/datetime/timestamp
+        - open: 开盘价
+        - high: 最高价
+        - low: 最低价
+        - close: 收盘价
+        - volume: 成交量
+        - finished: 是否完结(可选, 默认为true)
+        """
+
+    def import_book(self, code: Codes, path: str) -> int:
+        """
+        导入盘口快照文件, 按扩展名识别格式(csv/parquet/feather/arrow/ipc), 返回导入行数
+        - [`code`]: 交易对
+        - [`path`]: 文件路径
+
+        每行一个价格档位, 同一时间的行组成一个快照:
+        - time: 时间, 毫秒时间戳或ISO-8601(无时区按UTC处理)
+        - side: bid/ask
+        - price: 价格
+        - size: 挂单量
+        """
+
+    def import_trades(self, code: Codes, path: str) -> int:
+        """
+        导入逐笔成交文件, 按扩展名识别格式(csv/parquet/feather/arrow/ipc), 返回导入行数
+        - [`code`]: 交易对
+        - [`path`]: 文件路径
+
+        列:
+        - time: 时间, 毫秒时间戳或ISO-8601(无时区按UTC处理)
+        - side: 主动成交方向buy/sell
+        - price: 成交价
+        - size: 成交量
+        """
+
+    def load_book(
+        self,
+        code: Codes,
+        begin: Optional[str] = None,
+        end: Optional[str] = None,
+    ) -> DataFrame:
+        """
+        加载盘口快照
+        - [`code`]: 交易对
+        - [`begin`]: 开始时间
+        - [`end`]: 结束时间
+        - 返回列: time, side, price, size
+        """
+
+    def load_trades(
+        self,
+        code: Codes,
+        begin: Optional[str] = None,
+        end: Optional[str] = None,
+    ) -> DataFrame:
+        """
+        加载逐笔成交
+        - [`code`]: 交易对
+        - [`begin`]: 开始时间
+        - [`end`]: 结束时间
+        - 返回列: time, side, price, size
+        """
+
+    def list(self, market: Optional[Market] = None) -> DataFrame:
+        """
+        列出本地已缓存的交易对
+        - [`market`]: 市场, 为空时列出全部
+        - 返回列: code, market, interval, rows, begin, end, gaps, missing
+        """
+
+    def load(
+        self,
+        code: Codes,
+        begin: Optional[str] = None,
+        end: Optional[str] = None,
+        interval: Interval = Interval.Min,
+    ) -> DataFrame:
+        """
+        加载K线
+        - [`code`]: 交易对
+        - [`begin`]: 开始时间
+        - [`end`]: 结束时间
+        - [`interval`]: 周期, 本地没有该周期时由1分钟K线合成
+        """
+
+    def check(self, code: Codes, interval: Interval = Interval.Min) -> DataFrame:
+        """
+        检查数据完整性
+        - [`code`]: 交易对
+        - [`interval`]: 周期
+        - 返回列: check, count, passed
+        - checksum: 数据清单校验
+        - unordered: 时间非单调递增
+        - duplicates: 重复时间
+        - ohlc: 开高低收不一致
+        - non_positive: 价格非正数
+        - gaps: 数据缺口
+        """
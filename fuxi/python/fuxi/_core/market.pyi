@@ -1,6 +1,8 @@
+from typing import List, Optional, Tuple
 from .position import Position
 from .alias import Price, Size, Time
 from .code import Codes
+from .base import Side
 
 class Candle:
     """K线"""
@@ -38,6 +40,41 @@ class FundingRate:
     update_time: Time
     """更新时间"""
 
+class OrderBook:
+    """盘口快照"""
+
+    code: Codes
+    """交易对"""
+    time: Time
+    """时间"""
+    bids: List[Tuple[float, float]]
+    """买盘(价格, 数量), 价格从高到低"""
+    asks: List[Tuple[float, float]]
+    """卖盘(价格, 数量), 价格从低到高"""
+
+    def best_bid(self) -> Optional[Tuple[float, float]]:
+        """买一"""
+
+    def best_ask(self) -> Optional[Tuple[float, float]]:
+        """卖一"""
+
+    def mid(self) -> Optional[float]:
+        """中间价"""
+
+class Trade:
+    """逐笔成交"""
+
+    code: Codes
+    """交易对"""
+    time: Time
+    """时间"""
+    side: Side
+    """主动成交方向"""
+    price: float
+    """成交价"""
+    size: float
+    """成交量"""
+
 class Symbol:
     """交易对"""
 
@@ -49,3 +86,5 @@ class Symbol:
     """挂单费率"""
     position: Position
     """持仓"""
+    book: Optional[OrderBook]
+    """最新盘口快照, 仅回测开启盘口撮合且有盘口数据时可用"""
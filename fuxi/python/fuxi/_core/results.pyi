@@ -0,0 +1,55 @@
+from typing import List, Optional, Tuple
+from polars import DataFrame
+from .alias import Time
+from .order import Order
+
+class Run:
+    """已保存的回测结果"""
+
+    id: str
+    """运行ID"""
+    created: Time
+    """保存时间"""
+    version: str
+    """引擎版本"""
+    config: str
+    """回测配置(json)"""
+    orders: List[Order]
+    """全部订单"""
+    logs: List[str]
+    """输出的日志"""
+    metrics: DataFrame
+    """回测指标, 同`Backtest.metrics()`"""
+    equity: DataFrame
+    """权益曲线, 同`Backtest.equity()`"""
+    trades: DataFrame
+    """成交列表: time, id, code, method, direction, side, size, price, fee, remark"""
+
+class ResultStore:
+    """回测结果目录, 每次回测一个以运行ID命名的子目录"""
+
+    dir: str
+    """结果目录"""
+
+    def __init__(self, dir: Optional[str] = None):
+        """
+        初始化回测结果目录
+        - [`dir`]: 结果目录, 默认读取环境变量`FUXI_RESULTS_DIR`, 否则为当前目录下的`results`
+        """
+
+    def list(self) -> DataFrame:
+        """
+        已保存的回测结果, 每次运行一行, 按保存时间排序
+        - 列: id, created, version, begin, end, symbols + `Backtest.metrics()`的指标列
+        """
+
+    def load(self, id: str) -> Run:
+        """加载回测结果"""
+
+    def compare(self, ids: List[str]) -> Tuple[DataFrame, DataFrame]:
+        """
+        比较回测结果
+        - 返回: (指标, 权益曲线)
+            - 指标: 每次运行一行, id + `Backtest.metrics()`的指标列
+            - 权益曲线: id, time, equity, benchmark(设置基准时), 可以按id透视后对齐时间
+        """
@@ -1,11 +1,11 @@
 from abc import ABC
 from decimal import Decimal
-from typing import Dict, Optional
+from typing import Dict, List, Optional
 from pandas import DataFrame
-from ._core import Context, Codes, Mode, Volume, Symbol, LogLevel, Backtest, Timer, Order, Method, Direction, Side
+from ._core import Context, Codes, Mode, Volume, Symbol, Trade, LogLevel, Backtest, Order, Method, Direction, Side
 import polars as pl
 from polars import DataFrame
-from datetime import datetime
+from datetime import datetime, tzinfo
 
 
 class Strategy(ABC):
@@ -18,6 +18,11 @@ class Strategy(ABC):
         """模式"""
         return self._context.mode
 
+    @property
+    def tz(self) -> tzinfo:
+        """时区"""
+        return self._context.tz
+
     @property
     def time(self) -> datetime:
         """当前时间"""
@@ -62,21 +67,18 @@ class Strategy(ABC):
         """显示错误日志"""
         self._context.show_log(LogLevel.Error, *args)
 
-    @staticmethod
-    def millis_to_time(millis: int) -> datetime:
+    def millis_to_time(self, millis: int) -> datetime:
         """毫秒转换为时间"""
-        return Context.millis_to_time(millis)
+        return self._context.millis_to_time(millis)
 
     # ================================================================ #
     # 辅助API
     # ================================================================ #
-    @staticmethod
-    def nanos_to_time(nanos: int) -> datetime:
+    def nanos_to_time(self, nanos: int) -> datetime:
         """纳秒转换为时间"""
-        return Context.nanos_to_time(nanos)
+        return self._context.nanos_to_time(nanos)
 
-    @staticmethod
-    def str_to_time(s: str) -> datetime:
+    def str_to_time(self, s: str) -> datetime:
         """
         字符串转换为时间
 
@@ -95,8 +97,15 @@ class Strategy(ABC):
         - 202001020304
         - 20200102030405
         - 20200102030405678
+        - 2020-01-02T03:04:05 (ISO-8601, 与上面的格式一样按所在时区处理)
+        - 2020-01-02T03:04:05Z / 2020-01-02T03:04:05+08:00 (RFC3339, 带时区偏移)
+        - 1577934245 (秒时间戳, 10位且不是合法的紧凑格式时)
+        - @1577934245 (秒时间戳)
+        - 1577934245678 (毫秒时间戳, 13位)
+        - now (当前时间)
+        - 相对时间: 在以上任意格式后追加[+-]数字+单位(s/m/h/d/w), 可以叠加, 如now-30d, 2024-01-01+90d, now-1d+8h
         """
-        return Context.str_to_time(s)
+        return self._context.str_to_time(s)
 
     @staticmethod
     def time_to_str(t: datetime, fmt: str) -> str:
@@ -118,6 +127,28 @@ class Strategy(ABC):
         """生成唯一id"""
         return Context.new_id()
 
+    def add_timer(
+        self,
+        name: str,
+        every: Optional[str] = None,
+        cron: Optional[str] = None,
+        offset: Optional[str] = None,
+        tz: Optional[str] = None,
+    ):
+        """
+        添加定时器, 触发时以名称回调on_timer
+        - [`name`]: 定时器名称, 不能与已有定时器重复(内置: Daily/Hourly/Minutely)
+        - [`every`]: 固定周期, 数字+单位(s/m/h/d/w), 如5m, 4h, 1w(周一00:00对齐)
+        - [`cron`]: cron表达式(分 时 日 月 周), 如0 0 * * 1
+        - [`offset`]: 触发偏移, 格式同every, 如every=1d, offset=8h表示每天08:00
+        - [`tz`]: 对齐时区, 默认为上下文时区, 如UTC
+        """
+        self._context.add_timer(name, every, cron, offset, tz)
+
+    def remove_timer(self, name: str) -> bool:
+        """移除定时器, 返回是否存在"""
+        return self._context.remove_timer(name)
+
     def set_log_level(self, engine: LogLevel, strategy: LogLevel):
         """
         设置日志级别
@@ -149,10 +180,7 @@ class Strategy(ABC):
         获取K线
         - [`code`]: 交易对
         """
-        if self.mode == Mode.Backtest:
-            return self._candles[code].slice(0, self._backtest.offset)
-        else:
-            return self._candles[code]
+        return self._candles[code]
 
     # ================================================================ #
     # 订单API
@@ -307,8 +335,11 @@ class Strategy(ABC):
     def on_signal(self):
         """信号事件"""
 
-    def on_timer(self, timer: Timer):
-        """定时器事件"""
+    def on_trade(self, trade: Trade):
+        """逐笔成交事件, 仅回测开启逐笔回放时触发"""
+
+    def on_timer(self, name: str):
+        """定时器事件, [`name`]为定时器名称"""
 
     def on_position(self):
         """持仓事件"""
@@ -332,6 +363,11 @@ class Strategy(ABC):
         self._candles = {}
         self._signals = {}
 
+    def _subscriptions(self) -> List[str]:
+        """重写过的事件, 回测只回调这些事件"""
+        events = ["candle", "signal", "trade", "timer", "position", "order", "cash"]
+        return [event for event in events if getattr(type(self), f"on_{event}") is not getattr(Strategy, f"on_{event}")]
+
     def _on_inject_context(self, context: Context):
         self._context = context
 
@@ -351,11 +387,11 @@ class Strategy(ABC):
         if self.mode != Mode.Backtest:
             self.on_signal()
 
-    def _on_candle(self, code: Codes, candles: DataFrame):
+    def _on_candle(self, code: Codes, candles: DataFrame, notify: bool = True):
         df = (
             pl.concat(
                 [self._candles[code], candles],
-                how="horizontal",
+                how="vertical",
             )
             .unique(
                 subset=["time"],
@@ -365,14 +401,20 @@ class Strategy(ABC):
             .rechunk()
         )
         self._candles[code] = df
-        self.on_candle(code, df)
-        self.on_signal()
+        # 回测未重写on_candle时只追加K线, get_candle仍能取到最新K线
+        if notify:
+            self.on_candle(code, df)
+        if self.mode != Mode.Backtest:
+            self.on_signal()
 
     def _on_backtest_tick(self):
         self.on_signal()
 
-    def _on_timer(self, timer: Timer):
-        self.on_timer(timer)
+    def _on_trade(self, trade: Trade):
+        self.on_trade(trade)
+
+    def _on_timer(self, name: str):
+        self.on_timer(name)
 
     def _on_position(self):
         self.on_position()
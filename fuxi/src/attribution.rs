@@ -0,0 +1,210 @@
+use crate::{
+    backtest::Backtest,
+    types::{
+        alias::{Map, Price, Size},
+        base::{Codes, Direction, Side},
+        order::Order,
+    },
+};
+use anyhow::{Result, ensure};
+use polars::prelude::*;
+use pyo3::pymethods;
+use pyo3_polars::PyDataFrame;
+use rust_decimal::{
+    Decimal,
+    prelude::{FromPrimitive, ToPrimitive},
+};
+use std::collections::VecDeque;
+
+/// 归因维度
+const KEYS: [&str; 3] = ["code", "direction", "remark"];
+
+/// 盈亏归因的最小单位: 交易对 + 方向 + 备注
+pub type Key = (Codes, Direction, Option<String>);
+
+#[derive(Default, Clone)]
+pub struct Attribution {
+    pub realized: Size,
+    pub unrealized: Size,
+    pub fees: Size,
+    pub funding: Size,
+}
+
+impl Attribution {
+    #[inline]
+    pub fn net(&self) -> Size {
+        self.realized + self.unrealized - self.fees - self.funding
+    }
+}
+
+/// 未平仓的开仓成交
+struct Lot {
+    size: Size,
+    price: Price,
+    remark: Option<String>,
+}
+
+/// 按成交计算盈亏归因
+/// - 开仓成交按先进先出记为持仓批次, 平仓盈亏和期末浮动盈亏归到开仓订单的备注
+/// - 手续费归到产生手续费的订单的备注
+/// - 回测未加载资金费率, 资金费为0
+pub fn attribute(orders: &[Order], marks: &Map<Codes, Price>) -> Map<Key, Attribution> {
+    let mut orders = orders
+        .iter()
+        .filter(|order| *order.deal_size() > Decimal::ZERO)
+        .collect::<Vec<_>>();
+    orders.sort_by_key(|order| (*order.update_time(), order.id().clone()));
+
+    let mut result: Map<Key, Attribution> = Map::new();
+    let mut lots: Map<(Codes, Direction), VecDeque<Lot>> = Map::new();
+    for order in orders {
+        let code = *order.code();
+        let direction = *order.direction();
+        let remark = order.remark().clone();
+        let price = *order.deal_price();
+
+        result
+            .entry((code, direction, remark.clone()))
+            .or_default()
+            .fees += *order.deal_fee();
+
+        let queue = lots.entry((code, direction)).or_default();
+        let opening = matches!(
+            (direction, *order.side()),
+            (Direction::Long, Side::Buy) | (Direction::Short, Side::Sell)
+        );
+        if opening {
+            queue.push_back(Lot {
+                size: *order.deal_size(),
+                price,
+                remark,
+            });
+            continue;
+        }
+
+        let mut size = *order.deal_size();
+        while size > Decimal::ZERO {
+            let Some(lot) = queue.front_mut() else {
+                break;
+            };
+            let take = size.min(lot.size);
+            result
+                .entry((code, direction, lot.remark.clone()))
+                .or_default()
+                .realized += pnl(direction, lot.price, price, take);
+            lot.size -= take;
+            size -= take;
+            if lot.size.is_zero() {
+                queue.pop_front();
+            }
+        }
+    }
+
+    for ((code, direction), queue) in lots {
+        let Some(mark) = marks.get(&code) else {
+            continue;
+        };
+        for lot in queue {
+            result
+                .entry((code, direction, lot.remark))
+                .or_default()
+                .unrealized += pnl(direction, lot.price, *mark, lot.size);
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn pnl(direction: Direction, open: Price, close: Price, size: Size) -> Size {
+    match direction {
+        Direction::Long => (close - open) * size,
+        Direction::Short => (open - close) * size,
+    }
+}
+
+#[pymethods]
+impl Backtest {
+    /// 盈亏归因
+    #[pyo3(name = "attribution", signature = (by=vec!["code".to_string()]))]
+    fn py_attribution(&self, by: Vec<String>) -> Result<PyDataFrame> {
+        for key in &by {
+            ensure!(KEYS.contains(&key.as_str()), "无效的归因维度: {key}");
+        }
+
+        let items = self.attribution()?;
+        let value = |get: fn(&Attribution) -> Size| {
+            items
+                .values()
+                .map(|item| get(item).to_f64().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        let df = DataFrame::new(vec![
+            Column::new(
+                "code".into(),
+                items
+                    .keys()
+                    .map(|(code, ..)| code.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "direction".into(),
+                items
+                    .keys()
+                    .map(|(_, direction, _)| direction.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "remark".into(),
+                items
+                    .keys()
+                    .map(|(.., remark)| remark.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new("realized".into(), value(|item| item.realized)),
+            Column::new("unrealized".into(), value(|item| item.unrealized)),
+            Column::new("fees".into(), value(|item| item.fees)),
+            Column::new("funding".into(), value(|item| item.funding)),
+            Column::new("net".into(), value(Attribution::net)),
+        ])?;
+
+        let sums = ["realized", "unrealized", "fees", "funding", "net"].map(|name| col(name).sum());
+        let df = if by.is_empty() {
+            df.lazy().select(sums).collect()?
+        } else {
+            let by = by.iter().map(|key| col(key.as_str())).collect::<Vec<_>>();
+            df.lazy()
+                .group_by(by.clone())
+                .agg(sums)
+                .sort_by_exprs(by, SortMultipleOptions::default().with_nulls_last(true))
+                .collect()?
+        };
+        Ok(PyDataFrame(df))
+    }
+}
+
+impl Backtest {
+    pub fn attribution(&self) -> Result<Map<Key, Attribution>> {
+        Ok(attribute(&self.orders(), &self.marks()?))
+    }
+
+    /// 每个交易对最后回放的一分钟的收盘价, 用于计算期末浮动盈亏
+    fn marks(&self) -> Result<Map<Codes, Price>> {
+        let mut marks = Map::new();
+        let count = self.equity().len();
+        if count == 0 {
+            return Ok(marks);
+        }
+
+        // 权益曲线的第一个点对应回测开始时间的K线
+        let index = *self.history_size() - 1 + count - 1;
+
+        for (code, symbol) in self.context().symbols().maps().iter() {
+            let close = symbol.candles().column("close")?.f64()?.get(index);
+            if let Some(close) = close.and_then(Decimal::from_f64) {
+                marks.insert(*code, close);
+            }
+        }
+        Ok(marks)
+    }
+}
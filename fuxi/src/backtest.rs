@@ -1,22 +1,57 @@
 use crate::{
     context::Context,
-    history_data,
+    data_store::DataStore,
+    helpers::log::LogFile,
+    history_data::{self, DownloadOptions},
+    matching::{self, Replay},
+    metrics::{Metrics, Relative},
+    providers::{DataProvider, new_provider},
+    results::ResultStore,
     runtime::Runtime,
-    strategy::Strategy,
+    strategy::{Event, Strategy},
     types::{
-        alias::{Price, Size, Time},
-        base::{Codes, Direction, LogLevel, Market, Method, Side, Timer},
-        market::Symbol,
-        order::Order,
+        alias::{Map, Price, Size, Time},
+        base::{
+            Codes, DataFormat, DataSource, Direction, GapPolicy, Interval, LogLevel, Market,
+            Method, OrderStatus, Side, Timer, Volume,
+        },
+        market::{OrderBook, Symbol, Trade},
+        order::{Order, OrderData},
     },
 };
-use anyhow::{Result, ensure};
-use chrono::{Duration, Timelike};
+use anyhow::{Result, anyhow, ensure};
+use chrono::{Duration, DurationRound};
 use fuxi_macros::model;
+use polars::frame::DataFrame;
 use pyo3::{Bound, PyAny, pymethods};
 use pyo3_polars::PyDataFrame;
-use rust_decimal::{Decimal, dec};
-use std::{sync::Arc, time::Instant};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use rust_decimal::{
+    Decimal, dec,
+    prelude::{FromPrimitive, ToPrimitive},
+};
+use serde::Serialize;
+use std::{path::PathBuf, sync::Arc, time::Instant};
+
+/// 回测配置, 与结果一起记录, 相同的配置, 种子和数据得到相同的结果
+#[derive(Clone, Serialize)]
+pub struct Config {
+    pub begin: String,
+    pub end: String,
+    pub tz: String,
+    pub symbols: Vec<(Codes, Size, Size, Size)>,
+    pub spot: Size,
+    pub swap: Size,
+    pub history_size: usize,
+    pub gap_policy: GapPolicy,
+    pub data_source: DataSource,
+    pub book_fills: bool,
+    pub tick_replay: bool,
+    pub skip_idle: bool,
+    pub benchmark: Vec<Codes>,
+    pub seed: u64,
+}
 
 #[model(python)]
 pub struct Backtest {
@@ -26,14 +61,39 @@ pub struct Backtest {
     pub offset: usize,
     context: Context,
     strategy: Arc<Strategy>,
-    force_sync_data: bool,
+    download_options: DownloadOptions,
+    skip_failed_data: bool,
+    gap_policy: GapPolicy,
+    data_store: DataStore,
+    provider: Arc<dyn DataProvider>,
+    book_fills: bool,
+    books: Map<Codes, Replay<OrderBook>>,
+    queues: Map<String, f64>,
+    tick_replay: bool,
+    tick_codes: Vec<Codes>,
+    trades: Replay<Trade>,
+    skip_idle: bool,
+    delivered: usize,
+    equity: Vec<(i64, f64)>,
+    checkpoint: Option<PathBuf>,
+    checkpoint_every: Duration,
+    resume: Option<Time>,
+    debug: bool,
+    codes: Vec<Codes>,
+    cursor: Option<Time>,
+    saved: Time,
+    config: Config,
+    rng: ChaCha12Rng,
+    results: ResultStore,
+    save_results: bool,
+    run_id: Option<String>,
 }
 
 #[pymethods]
 impl Backtest {
     #[allow(clippy::too_many_arguments)]
     #[new]
-    #[pyo3(signature = (strategy, begin, end, symbols, spot = dec!(1000), swap = dec!(1000), history_size=5000, force_sync_data=false))]
+    #[pyo3(signature = (strategy, begin, end, symbols, spot = dec!(1000), swap = dec!(1000), history_size=5000, force_sync_data=false, gap_policy=GapPolicy::Fill, data_dir=None, data_format=DataFormat::Ipc, data_source=DataSource::Github, data_source_path=None, download_concurrency=4, download_retries=3, download_timeout=60, skip_failed_data=false, tz="Asia/Shanghai", book_fills=false, tick_replay=false, skip_idle=false, checkpoint=None, checkpoint_every="1d", debug=false, seed=None, benchmark=None, results_dir=None, save_results=true))]
     fn new(
         strategy: &Bound<PyAny>,
         begin: &str,
@@ -43,16 +103,59 @@ impl Backtest {
         swap: Size,
         history_size: usize,
         force_sync_data: bool,
+        gap_policy: GapPolicy,
+        data_dir: Option<&str>,
+        data_format: DataFormat,
+        data_source: DataSource,
+        data_source_path: Option<&str>,
+        download_concurrency: usize,
+        download_retries: usize,
+        download_timeout: u64,
+        skip_failed_data: bool,
+        tz: &str,
+        book_fills: bool,
+        tick_replay: bool,
+        skip_idle: bool,
+        checkpoint: Option<&str>,
+        checkpoint_every: &str,
+        debug: bool,
+        seed: Option<u64>,
+        benchmark: Option<Vec<Codes>>,
+        results_dir: Option<&str>,
+        save_results: bool,
     ) -> Result<Self> {
         let strategy = Strategy::new(strategy)?;
 
         let context = Context::default();
 
-        let begin = crate::helpers::time::str_to_time(begin)?;
-        let end = crate::helpers::time::str_to_time(end)?;
+        let tz = crate::helpers::time::parse_tz(tz)?;
+        context.set_tz(tz);
+
+        let begin =
+            crate::helpers::time::str_to_time(begin, &tz)?.duration_trunc(Duration::minutes(1))?;
+        let end =
+            crate::helpers::time::str_to_time(end, &tz)?.duration_trunc(Duration::minutes(1))?;
         ensure!(begin < end, "开始时间不能大于结束时间: {begin} - {end}");
         context.set_time(begin);
 
+        let seed = seed.unwrap_or_else(rand::random::<u64>);
+        let config = Config {
+            begin: begin.to_rfc3339(),
+            end: end.to_rfc3339(),
+            tz: tz.name().to_string(),
+            symbols: symbols.clone(),
+            spot,
+            swap,
+            history_size,
+            gap_policy,
+            data_source,
+            book_fills,
+            tick_replay,
+            skip_idle,
+            benchmark: benchmark.unwrap_or_default(),
+            seed,
+        };
+
         for (code, taker, maker, lever) in symbols {
             ensure!(
                 !context.symbols().maps().contains_key(&code),
@@ -69,6 +172,13 @@ impl Backtest {
                 .insert(code, Symbol::new(code, taker, maker, lever));
         }
 
+        for code in &config.benchmark {
+            ensure!(
+                context.symbols().maps().contains_key(code),
+                "基准交易对需要包含在交易对中: {code}"
+            );
+        }
+
         ensure!(
             !(spot.is_zero() && swap.is_zero()),
             "现货&合约的资金不能同时为空"
@@ -82,6 +192,28 @@ impl Backtest {
 
         ensure!(history_size > 0, "历史数据长度错误: {history_size}");
 
+        let checkpoint_every = crate::helpers::time::parse_duration(checkpoint_every)?;
+        ensure!(
+            checkpoint_every >= Duration::minutes(1),
+            "检查点间隔错误: {checkpoint_every}"
+        );
+
+        let data_store = DataStore::new(data_dir, data_format, tz)?;
+        let results = ResultStore::new(results_dir)?;
+        if save_results {
+            context.set_logs(Some(LogFile::create(&results.dir())?));
+        }
+        ensure!(
+            download_concurrency > 0,
+            "下载并发数错误: {download_concurrency}"
+        );
+        ensure!(download_timeout > 0, "下载超时时间错误: {download_timeout}");
+        let provider = new_provider(
+            data_source,
+            data_source_path,
+            std::time::Duration::from_secs(download_timeout),
+        )?;
+
         let backtest = Backtest::from(BacktestData {
             begin,
             end,
@@ -89,7 +221,36 @@ impl Backtest {
             offset: history_size,
             context: context.clone(),
             strategy: strategy.clone(),
-            force_sync_data,
+            download_options: DownloadOptions {
+                force: force_sync_data,
+                concurrency: download_concurrency,
+                retries: download_retries,
+            },
+            skip_failed_data,
+            gap_policy,
+            data_store,
+            provider,
+            book_fills,
+            books: Default::default(),
+            queues: Default::default(),
+            tick_replay,
+            tick_codes: Default::default(),
+            trades: Replay::new(vec![]),
+            skip_idle,
+            delivered: history_size,
+            equity: Default::default(),
+            checkpoint: checkpoint.map(PathBuf::from),
+            checkpoint_every,
+            resume: None,
+            debug,
+            codes: Default::default(),
+            cursor: None,
+            saved: begin,
+            config,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            results,
+            save_results,
+            run_id: None,
         });
 
         backtest
@@ -100,12 +261,52 @@ impl Backtest {
         strategy.on_inject_backtest(backtest.clone())?;
         strategy.on_init()?;
 
+        // 订阅定时器时Minutely每分钟触发, 跳过空闲分钟不会生效
+        let minutely = Timer::Minutely.to_string();
+        ensure!(
+            !(skip_idle
+                && strategy.subscribes(Event::Timer)
+                && context.scheduler().contains(&minutely)),
+            "跳过空闲分钟时不能使用{minutely}定时器, 请在on_init中调用remove_timer(\"{minutely}\")"
+        );
+
         Ok(backtest)
     }
 
+    /// 权益曲线
+    #[pyo3(name = "equity")]
+    fn py_equity(&self) -> Result<PyDataFrame> {
+        Ok(PyDataFrame(self.equity_frame()?))
+    }
+
+    /// 随机数种子
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.config().seed
+    }
+
+    /// 回测配置(json)
+    #[pyo3(name = "config")]
+    fn py_config(&self) -> Result<String> {
+        Ok(serde_json::to_string(&*self.config())?)
+    }
+
+    #[pyo3(name = "metrics")]
+    fn py_metrics(&self) -> Result<PyDataFrame> {
+        Ok(PyDataFrame(self.metrics_frame()?))
+    }
+
     fn launche(&self) -> Result<()> {
         crate::helpers::log::init(Some(1024));
-        self.run()?;
+        if *self.debug() {
+            let codes = self.sync_data()?;
+            self.prepare(&codes, &self.load_frames(&codes)?)?;
+        } else {
+            self.run()?;
+            if *self.save_results() {
+                self.save_run()?;
+            }
+        }
         crate::helpers::log::flush()?;
         Ok(())
     }
@@ -113,10 +314,185 @@ impl Backtest {
 
 impl Runtime for Backtest {
     fn run(&self) -> Result<()> {
-        let start_time = Instant::now();
+        let codes = self.sync_data()?;
+        self.simulate(&codes, &self.load_frames(&codes)?)
+    }
+
+    fn place_order(
+        &self,
+        code: Codes,
+        method: Method,
+        direction: Direction,
+        side: Side,
+        size: Size,
+        price: Price,
+        remark: Option<String>,
+    ) -> Result<Order> {
+        let symbol = self.symbol(code)?;
+        ensure!(size > Decimal::ZERO, "订单数量错误: {size}");
+        ensure!(
+            size.round_dp(code.decimals().to_u32().unwrap_or_default()) == size,
+            "订单数量精度错误: 交易对={code}, 数量={size}"
+        );
+        ensure!(
+            method == Method::Market || price > Decimal::ZERO,
+            "订单价格错误: {price}"
+        );
+        ensure!(
+            code.is_swap() || direction == Direction::Long,
+            "现货不支持做空: {code}"
+        );
+
+        let now = *self.context().time();
+        // 订单id取自按种子初始化的随机数生成器, 相同种子的回测订单完全一致
+        let id = crate::helpers::id::from_rng(&mut *self.rng_mut());
+        let order = Order::from(OrderData {
+            code,
+            id,
+            method,
+            direction,
+            side,
+            status: OrderStatus::Pending,
+            size,
+            price,
+            deal_size: Decimal::ZERO,
+            deal_price: Decimal::ZERO,
+            deal_fee: Decimal::ZERO,
+            margin: Decimal::ZERO,
+            remark,
+            create_time: now,
+            update_time: now,
+        });
+
+        // 开仓冻结保证金, 平仓冻结持仓, 不足时拒绝
+        let position = symbol.position().clone();
+        let account = self.account(code);
+        if order.is_open() {
+            let price = match method {
+                Method::Limit => price,
+                Method::Market => self
+                    .last_close(&symbol)?
+                    .ok_or(anyhow!("没有可用的价格: {code}"))?,
+            };
+            let margin = price * size / *position.lever();
+            if *account.avail() >= margin {
+                order.set_margin(margin);
+                *account.avail_mut() -= margin;
+                *account.frozen_mut() += margin;
+                *position.margin().frozen_mut() += margin;
+            } else {
+                order.set_status(OrderStatus::Rejected);
+            }
+        } else {
+            let side = position.side(direction);
+            if *side.size().avail() >= size {
+                *side.size().avail_mut() -= size;
+                *side.size().frozen_mut() += size;
+            } else {
+                order.set_status(OrderStatus::Rejected);
+            }
+        }
+        let rejected = *order.status() == OrderStatus::Rejected;
+        if rejected {
+            self.context().show_log(
+                LogLevel::Warn,
+                format_args!(
+                    "下单被拒绝 交易对: {code}, 方向: {direction}, 买卖: {side}, 数量: {size}, 原因: {}",
+                    if order.is_open() {
+                        "可用资金不足"
+                    } else {
+                        "可平持仓不足"
+                    }
+                ),
+            );
+        }
+        position
+            .orders()
+            .maps_mut()
+            .insert(order.id().clone(), order.clone());
+
+        let strategy = self.strategy().clone();
+        strategy.on_order()?;
+        if !rejected {
+            if order.is_open() {
+                strategy.on_cash()?;
+            } else {
+                strategy.on_position()?;
+            }
+        }
+
+        Ok(order)
+    }
+
+    fn cancel_order(&self, code: Codes, id: &str) -> Result<()> {
+        let symbol = self.symbol(code)?;
+        let position = symbol.position().clone();
+        let order = position
+            .orders()
+            .maps()
+            .get(id)
+            .cloned()
+            .ok_or(anyhow!("订单不存在: 交易对={code}, id={id}"))?;
+        let status = *order.status();
+        ensure!(
+            status == OrderStatus::Pending,
+            "订单不能取消: id={id}, 状态={status}"
+        );
+
+        // 释放未成交部分冻结的保证金或持仓
+        let frozen = frozen_margin(&order);
+        let remaining = *order.size() - *order.deal_size();
+        order.set_status(OrderStatus::Cancelled);
+        order.set_update_time(*self.context().time());
+        self.queues_mut().shift_remove(id);
+
         let strategy = self.strategy().clone();
+        strategy.on_order()?;
+        if order.is_open() {
+            let account = self.account(code);
+            *account.frozen_mut() -= frozen;
+            *account.avail_mut() += frozen;
+            *position.margin().frozen_mut() -= frozen;
+            strategy.on_cash()?;
+        } else {
+            let side = position.side(*order.direction());
+            *side.size().frozen_mut() -= remaining;
+            *side.size().avail_mut() += remaining;
+            strategy.on_position()?;
+        }
+
+        Ok(())
+    }
+
+    fn set_lever(&self, code: Codes, lever: Size) -> Result<()> {
+        let symbol = self.symbol(code)?;
+        ensure!(code.is_swap(), "现货不支持杠杆: {code}");
+        ensure!(
+            lever >= Decimal::ONE && lever.fract().is_zero() && lever <= code.max_lever(),
+            "杠杆倍率错误: 交易对={code}, 杠杆倍率={lever}, 最大={}",
+            code.max_lever()
+        );
+
+        // 保证金按开仓时的杠杆计算, 有持仓或挂单时不能修改
+        let position = symbol.position().clone();
+        let busy = !position.long().size().total().is_zero()
+            || !position.short().size().total().is_zero()
+            || position
+                .orders()
+                .maps()
+                .values()
+                .any(|order| *order.status() == OrderStatus::Pending);
+        ensure!(!busy, "有持仓或挂单时不能修改杠杆: {code}");
+        position.set_lever(lever);
+
+        Ok(())
+    }
+}
 
-        let codes = self
+impl Backtest {
+    /// 同步历史数据, 返回可用的交易对
+    pub fn sync_data(&self) -> Result<Vec<Codes>> {
+        let mut codes = self
             .context()
             .symbols()
             .maps()
@@ -124,29 +500,51 @@ impl Runtime for Backtest {
             .cloned()
             .collect::<Vec<_>>();
 
-        history_data::download(self.context().clone(), &codes, *self.force_sync_data())?;
+        let report = history_data::download(
+            self.context().clone(),
+            self.data_store().clone(),
+            self.provider().clone(),
+            &codes,
+            *self.begin() - Duration::minutes(*self.history_size() as i64),
+            *self.end(),
+            self.download_options().clone(),
+        )?;
 
-        self.load_candles(&codes)?;
-
-        let mut now = *self.begin();
-        let end = *self.end();
-
-        while now <= end {
-            self.context().set_time(now);
+        if !report.failed.is_empty() {
+            ensure!(
+                *self.skip_failed_data(),
+                "下载历史数据失败: {}",
+                report.failed_summary()
+            );
+            for (code, _) in &report.failed {
+                // 强制同步时下载前没有校验本地数据
+                let usable = report.verified.contains(code)
+                    || (self.download_options().force
+                        && self.data_store().verify(*code, Interval::Min).is_ok());
+                if usable {
+                    self.context().show_log(
+                        LogLevel::Warn,
+                        format_args!("同步失败, 使用本地已有数据 交易对: {code}"),
+                    );
+                    continue;
+                }
+                self.context()
+                    .show_log(LogLevel::Warn, format_args!("同步失败, 移除交易对: {code}"));
+                self.context().symbols().maps_mut().shift_remove(code);
+                codes.retain(|item| item != code);
+            }
+            ensure!(!codes.is_empty(), "没有可用的交易对");
+        }
 
-            strategy.on_backtest_tick()?;
+        Ok(codes)
+    }
 
-            strategy.on_timer(Timer::Minutely)?;
-            if now.minute() == 0 {
-                strategy.on_timer(Timer::Hourly)?;
-            }
-            if now.minute() == 0 && now.hour() == 0 {
-                strategy.on_timer(Timer::Daily)?;
-            }
+    /// 加载已同步的数据并逐分钟回放
+    pub fn simulate(&self, codes: &[Codes], frames: &Map<Codes, DataFrame>) -> Result<()> {
+        let start_time = Instant::now();
 
-            now += Duration::minutes(1);
-            *self.offset_mut() += 1;
-        }
+        self.prepare(codes, frames)?;
+        while self.step()?.is_some() {}
 
         let elapsed = start_time.elapsed();
         self.context().show_log(
@@ -157,40 +555,206 @@ impl Runtime for Backtest {
         Ok(())
     }
 
-    #[allow(unused_variables)]
-    fn place_order(
-        &self,
-        code: Codes,
-        method: Method,
-        direction: Direction,
-        side: Side,
-        size: Size,
-        price: Price,
-        remark: Option<String>,
-    ) -> Result<Order> {
-        todo!()
+    /// 安装K线, 加载盘口和逐笔成交, 定位到第一个待回放的分钟
+    pub fn prepare(&self, codes: &[Codes], frames: &Map<Codes, DataFrame>) -> Result<()> {
+        self.context()
+            .symbols()
+            .maps_mut()
+            .retain(|code, _| codes.contains(code));
+
+        // 先取出恢复时间再访问其他字段, 同一把锁上读写会死锁
+        let (begin, offset) = (*self.begin(), *self.offset());
+        let resume = (*self.resume()).filter(|time| *time > begin);
+
+        // 已推送给策略的K线行数: 当前偏移量的K线在下一次回放时完结
+        let delivered = if resume.is_some() { offset - 1 } else { offset };
+        self.set_delivered(delivered);
+        for code in codes {
+            let df = frames
+                .get(code)
+                .cloned()
+                .ok_or(anyhow!("缺少K线数据: {code}"))?;
+            self.install_candles(*code, df)?;
+        }
+        if *self.book_fills() {
+            self.load_books(codes)?;
+        }
+        if *self.tick_replay() {
+            self.load_trades(codes)?;
+        }
+
+        // 从检查点恢复时跳过已回放的分钟, 盘口和逐笔成交推进到最后回放的分钟
+        let now = resume.unwrap_or(begin);
+        if let Some(time) = resume {
+            let last = time - Duration::minutes(1);
+            for replay in self.books_mut().values_mut() {
+                replay.advance(&last);
+            }
+            self.trades_mut().advance(&last);
+        }
+
+        self.set_codes(codes.to_vec());
+        self.set_cursor(Some(now));
+        self.set_saved(now);
+
+        Ok(())
     }
 
-    #[allow(unused_variables)]
-    fn cancel_order(&self, code: Codes, id: &str) -> Result<()> {
-        todo!()
+    /// 回放到下一个有事件的分钟, 返回本分钟成交的订单数, 回放结束返回None
+    /// - 没有事件的分钟只在引擎内推进: 记录权益和保存检查点, 不回调策略
+    pub fn step(&self) -> Result<Option<usize>> {
+        let now = loop {
+            let Some(now) = *self.cursor() else {
+                return Ok(None);
+            };
+            if now > *self.end() {
+                return Ok(None);
+            }
+            if self.has_events(now)? {
+                break now;
+            }
+            self.context().set_time(now);
+            self.advance_books(now);
+            self.finish_minute(now)?;
+        };
+
+        let strategy = self.strategy().clone();
+        let codes = self.codes().clone();
+
+        // 本分钟的事件数: 逐笔成交, 订单成交, 定时器
+        let mut events = 0;
+        let mut fills = 0;
+
+        if *self.tick_replay() {
+            let (trades, filled) = self.replay_trades(now)?;
+            events += trades;
+            fills += filled;
+        }
+        self.context().set_time(now);
+
+        if *self.offset() > *self.history_size() {
+            fills += self.match_orders(&codes)?;
+            self.emit_candles(&codes, strategy.subscribes(Event::Candle))?;
+        }
+        events += fills;
+
+        let timers = self.context().scheduler().due(&now);
+        if strategy.subscribes(Event::Timer) {
+            events += timers.len();
+        }
+
+        if strategy.subscribes(Event::Signal) && (!*self.skip_idle() || events > 0) {
+            strategy.on_backtest_tick()?;
+        }
+
+        for name in timers {
+            strategy.on_timer(&name)?;
+        }
+
+        self.finish_minute(now)?;
+
+        Ok(Some(fills))
     }
 
-    #[allow(unused_variables)]
-    fn set_lever(&self, code: Codes, lever: Size) -> Result<()> {
-        todo!()
+    /// 本分钟是否有需要回调策略或撮合的事件: K线完结, 定时器触发, 订单触发, 逐笔成交
+    fn has_events(&self, now: Time) -> Result<bool> {
+        let strategy = self.strategy().clone();
+        if strategy.subscribes(Event::Signal) && !*self.skip_idle() {
+            return Ok(true);
+        }
+        if *self.tick_replay() && self.trades().next_time().is_some_and(|time| time <= now) {
+            return Ok(true);
+        }
+        if strategy.subscribes(Event::Timer) && !self.context().scheduler().due(&now).is_empty() {
+            return Ok(true);
+        }
+
+        let index = *self.offset() as i64 - 1;
+        let candles = *self.offset() > *self.history_size();
+        let codes = self.codes().clone();
+        for code in &codes {
+            let symbol = self.context().symbols().maps().get(code).unwrap().clone();
+            let candle = symbol.candles().slice(index, 1);
+            if candles
+                && strategy.subscribes(Event::Candle)
+                && candle.column("finished")?.bool()?.get(0) == Some(true)
+            {
+                return Ok(true);
+            }
+
+            let orders = symbol
+                .position()
+                .orders()
+                .maps()
+                .values()
+                .filter(|order| *order.status() == OrderStatus::Pending)
+                .cloned()
+                .collect::<Vec<_>>();
+            if orders.is_empty() || self.tick_codes().contains(code) {
+                continue;
+            }
+            if let Some(replay) = self.books().get(code) {
+                if replay.next_time().is_some_and(|time| time <= now) {
+                    return Ok(true);
+                }
+                continue;
+            }
+            if !candles {
+                continue;
+            }
+            let value =
+                |name: &str| -> Result<Option<f64>> { Ok(candle.column(name)?.f64()?.get(0)) };
+            let (Some(high), Some(low), Some(close)) =
+                (value("high")?, value("low")?, value("close")?)
+            else {
+                continue;
+            };
+            if orders
+                .iter()
+                .any(|order| matching::match_candle(order, high, low, close).is_some())
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
     }
-}
 
-impl Backtest {
-    fn load_candles(&self, codes: &[Codes]) -> Result<()> {
-        use polars::prelude::*;
+    /// 结束一分钟: 记录权益, 推进到下一分钟, 按间隔保存检查点
+    fn finish_minute(&self, now: Time) -> Result<()> {
+        let codes = self.codes().clone();
+        self.record_equity(&codes, now)?;
 
-        let dir = std::env::current_dir()?.join("data");
-        let spot_dir = dir.join("spot");
-        let swap_dir = dir.join("swap");
+        let next = now + Duration::minutes(1);
+        self.set_cursor(Some(next));
+        *self.offset_mut() += 1;
 
-        let strategy = self.strategy().clone();
+        // 检查点记录下一个待回放的分钟; 先释放读锁, 保存后还要写入saved
+        let path = self.checkpoint().clone();
+        let due = next - *self.saved() >= *self.checkpoint_every();
+        if let Some(path) = path
+            && due
+        {
+            self.save_checkpoint(&path)?;
+            self.set_saved(next);
+        }
+
+        Ok(())
+    }
+
+    /// 加载[`sync_data`]返回的交易对, 数据已在同步时校验, 这里不再计算校验和
+    pub fn load_frames(&self, codes: &[Codes]) -> Result<Map<Codes, DataFrame>> {
+        codes
+            .iter()
+            .map(|code| Ok((*code, self.load_frame(*code)?)))
+            .collect()
+    }
+
+    /// 加载K线并按缺失数据处理方式补齐到每分钟
+    fn load_frame(&self, code: Codes) -> Result<DataFrame> {
+        use polars::prelude::*;
+
+        let start_time = Instant::now();
 
         let time_range = date_range(
             "time".into(),
@@ -199,67 +763,966 @@ impl Backtest {
             Duration::parse("1m"),
             ClosedWindow::Both,
             TimeUnit::Nanoseconds,
-            Some(&chrono_tz::Asia::Shanghai),
+            Some(&*self.context().tz()),
         )?
         .into_column();
         let time_range = DataFrame::new(vec![time_range])?.lazy();
 
-        for code in codes {
-            let start_time = Instant::now();
-
-            let file_path = match code.market() {
-                Market::Spot => spot_dir.join(format!(
-                    "{}.feather",
-                    code.code().replace("/", "_").replace(":", "_")
-                )),
-                Market::Swap => swap_dir.join(format!(
-                    "{}.feather",
-                    code.code().replace("/", "_").replace(":", "_")
-                )),
-            };
+        history_data::check_range(
+            self.context(),
+            self.data_store(),
+            code,
+            *self.begin() - chrono::Duration::minutes(*self.history_size() as i64),
+            *self.end(),
+        )?;
 
-            let df = LazyFrame::scan_ipc(
-                PlPathRef::from_local_path(file_path.as_path()).into_owned(),
-                Default::default(),
-            )?;
+        let df = self.data_store().scan(
+            code,
+            Interval::Min,
+            Some(*self.begin() - chrono::Duration::minutes(*self.history_size() as i64)),
+            Some(*self.end()),
+        )?;
 
-            let mut df = time_range
-                .clone()
-                .left_join(df, col("time"), col("time"))
+        let df = time_range
+            .left_join(df, col("time"), col("time"))
+            .with_column(col("close").is_null().alias("missing"));
+
+        let mut df = match *self.gap_policy() {
+            GapPolicy::Fill => df
+                .with_column(col("close").fill_null_with_strategy(FillNullStrategy::Forward(None)))
                 .with_columns([
-                    col("open").fill_null(0.0),
-                    col("high").fill_null(0.0),
-                    col("low").fill_null(0.0),
-                    col("close").fill_null(0.0),
+                    col("open").fill_null(col("close")),
+                    col("high").fill_null(col("close")),
+                    col("low").fill_null(col("close")),
                     col("volume").fill_null(0.0),
                     col("finished").fill_null(true),
-                ])
-                .collect()?;
+                ]),
+            GapPolicy::Null => df.with_column(col("finished").fill_null(true)),
+            GapPolicy::Missing => df.with_column(col("finished").fill_null(false)),
+        }
+        .collect()?;
+
+        let gaps = history_data::find_gaps(&df, &self.context().tz())?;
+        let _ = df.drop_in_place("missing")?;
+
+        if df.should_rechunk() {
+            df.rechunk_mut();
+        }
+
+        history_data::report_gaps(self.context(), code, df.height(), &gaps);
+
+        let elapsed = start_time.elapsed();
+
+        self.context().show_log(
+            LogLevel::Debug,
+            format_args!(
+                "加载数据完成 交易对: {code}, 耗时: {}, 数据: {df}",
+                humantime::format_duration(elapsed),
+            ),
+        );
 
-            if df.should_rechunk() {
-                df.rechunk_mut();
+        Ok(df)
+    }
+
+    /// 推送截止到已推送位置的历史K线, 从检查点恢复时包含开始时间到检查点之间的K线
+    fn install_candles(&self, code: Codes, df: DataFrame) -> Result<()> {
+        let history_size = *self.history_size();
+        let start = *self.delivered() - history_size;
+        self.strategy()
+            .clone()
+            .on_history_candle(code, PyDataFrame(df.slice(start as i64, history_size)))?;
+
+        self.context()
+            .symbols()
+            .maps()
+            .get(&code)
+            .unwrap()
+            .set_candles(df);
+
+        Ok(())
+    }
+
+    /// 按当前K线收盘价计算持仓浮动盈亏, 记录权益
+    fn record_equity(&self, codes: &[Codes], now: Time) -> Result<()> {
+        let index = *self.offset() - 1;
+        let mut equity = *self.context().spot().total() + *self.context().swap().total();
+
+        for code in codes {
+            let symbol = self.context().symbols().maps().get(code).unwrap().clone();
+            let close = symbol.candles().column("close")?.f64()?.get(index);
+            let Some(close) = close.and_then(Decimal::from_f64) else {
+                continue;
+            };
+
+            let position = symbol.position().clone();
+            let long = position.long().clone();
+            let short = position.short().clone();
+            let long_pnl = (close - *long.price()) * *long.size().total();
+            let short_pnl = (*short.price() - close) * *short.size().total();
+            long.pnl().set_unrealized(long_pnl);
+            short.pnl().set_unrealized(short_pnl);
+            position.pnl().set_unrealized(long_pnl + short_pnl);
+
+            equity += long_pnl + short_pnl;
+        }
+
+        self.equity_mut().push((
+            now.timestamp_nanos_opt().unwrap_or_default(),
+            equity.to_f64().unwrap_or_default(),
+        ));
+
+        Ok(())
+    }
+
+    /// 回测指标, 一行: seed + 指标 + 相对基准的指标(设置基准时)
+    pub fn metrics_frame(&self) -> Result<DataFrame> {
+        let mut columns = vec![polars::prelude::Column::new(
+            "seed".into(),
+            vec![self.config().seed],
+        )];
+        columns.extend(Metrics::to_columns(&[self.metrics()]));
+        if let Some(benchmark) = self.benchmark()? {
+            columns.extend(
+                Relative::compute(&self.equity(), &benchmark, &self.context().tz()).to_columns(),
+            );
+        }
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// 权益曲线: time, equity, benchmark(设置基准时)
+    pub fn equity_frame(&self) -> Result<DataFrame> {
+        let equity = self.equity().clone();
+        let mut columns = vec![
+            polars::prelude::Column::new(
+                "time".into(),
+                equity.iter().map(|(time, _)| *time).collect::<Vec<_>>(),
+            )
+            .cast(&self.data_store().time_type())?,
+            polars::prelude::Column::new(
+                "equity".into(),
+                equity.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+            ),
+        ];
+        if let Some(benchmark) = self.benchmark()? {
+            columns.push(polars::prelude::Column::new(
+                "benchmark".into(),
+                benchmark
+                    .iter()
+                    .map(|(_, value)| *value)
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        Ok(DataFrame::new(columns)?)
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics::compute(&self.equity(), &self.orders(), &self.context().tz())
+    }
+
+    /// 基准权益曲线: 以策略的期初权益在第一分钟等权买入基准交易对并持有, 没有设置基准时返回None
+    pub fn benchmark(&self) -> Result<Option<Vec<(i64, f64)>>> {
+        let codes = self.config().benchmark.clone();
+        let equity = self.equity().clone();
+        let Some((_, initial)) = equity.first() else {
+            return Ok(None);
+        };
+        if codes.is_empty() {
+            return Ok(None);
+        }
+
+        // 权益曲线的第一个点对应回测开始时间的K线
+        let start = *self.history_size() - 1;
+        let weight = 1.0 / codes.len() as f64;
+        let mut ratios = vec![0.0; equity.len()];
+        for code in &codes {
+            let symbol = self
+                .context()
+                .symbols()
+                .maps()
+                .get(code)
+                .cloned()
+                .ok_or(anyhow!("基准交易对不存在: {code}"))?;
+            let candles = symbol.candles().clone();
+            let closes = candles.column("close")?.f64()?;
+
+            // 缺失的收盘价沿用上一个收盘价, 第一个有效收盘价之前按期初计
+            let mut base = None;
+            let mut last = None;
+            for (index, ratio) in ratios.iter_mut().enumerate() {
+                if let Some(close) = closes.get(start + index).filter(|close| *close > 0.0) {
+                    base.get_or_insert(close);
+                    last = Some(close);
+                }
+                *ratio += weight
+                    * match (base, last) {
+                        (Some(base), Some(last)) => last / base,
+                        _ => 1.0,
+                    };
             }
+        }
 
-            let elapsed = start_time.elapsed();
+        Ok(Some(
+            equity
+                .iter()
+                .zip(ratios)
+                .map(|((time, _), ratio)| (*time, initial * ratio))
+                .collect(),
+        ))
+    }
+
+    /// 所有交易对的订单
+    pub fn orders(&self) -> Vec<Order> {
+        self.context()
+            .symbols()
+            .maps()
+            .values()
+            .flat_map(|symbol| {
+                symbol
+                    .position()
+                    .orders()
+                    .maps()
+                    .values()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn load_books(&self, codes: &[Codes]) -> Result<()> {
+        for code in codes {
+            if self.data_store().find_tick(*code, "book").is_none() {
+                self.context().show_log(
+                    LogLevel::Warn,
+                    format_args!("没有盘口数据, 使用K线撮合 交易对: {code}"),
+                );
+                continue;
+            }
 
+            let books = self
+                .data_store()
+                .load_books(*code, *self.begin(), *self.end())?;
             self.context().show_log(
                 LogLevel::Debug,
-                format_args!(
-                    "加载数据完成 交易对: {code}, 耗时: {}, 数据: {df}",
-                    humantime::format_duration(elapsed),
-                ),
+                format_args!("加载盘口数据完成 交易对: {code}, 快照: {}", books.len()),
+            );
+            self.books_mut().insert(*code, Replay::new(books));
+        }
+
+        Ok(())
+    }
+
+    fn load_trades(&self, codes: &[Codes]) -> Result<()> {
+        let mut trades = vec![];
+        for code in codes {
+            if self.data_store().find_tick(*code, "trades").is_none() {
+                self.context().show_log(
+                    LogLevel::Warn,
+                    format_args!("没有逐笔成交数据, 使用1分钟K线回放 交易对: {code}"),
+                );
+                continue;
+            }
+
+            let items = self
+                .data_store()
+                .load_trades(*code, *self.begin(), *self.end())?;
+            self.context().show_log(
+                LogLevel::Debug,
+                format_args!("加载逐笔成交完成 交易对: {code}, 成交: {}", items.len()),
             );
+            trades.extend(items);
+            self.tick_codes_mut().push(*code);
+        }
+        trades.sort_by_key(|trade| *trade.time());
+        self.set_trades(Replay::new(trades));
+
+        Ok(())
+    }
+
+    /// 回放截止到指定时间的逐笔成交, 上下文时间随成交推进, 返回成交笔数和其中撮合的订单数
+    fn replay_trades(&self, until: Time) -> Result<(usize, usize)> {
+        let strategy = self.strategy().clone();
+        let (_, trades) = self.trades_mut().advance(&until);
+        let count = trades.len();
+        let mut fills = 0;
+
+        for trade in trades {
+            let code = *trade.code();
+            let now = *trade.time();
+            self.context().set_time(now);
+
+            let symbol = self.context().symbols().maps().get(&code).unwrap().clone();
+            let orders = symbol
+                .position()
+                .orders()
+                .maps()
+                .values()
+                .filter(|order| *order.status() == OrderStatus::Pending)
+                .cloned()
+                .collect::<Vec<_>>();
+            for order in &orders {
+                let fill = {
+                    let mut queues = self.queues_mut();
+                    let queue = queues.entry(order.id().clone()).or_insert_with(|| {
+                        symbol
+                            .book()
+                            .as_ref()
+                            .map(|book| matching::queue_ahead(order, book))
+                            .unwrap_or_default()
+                    });
+                    matching::match_trade(order, queue, &trade)
+                };
+                if let Some(fill) = fill {
+                    self.apply_fill(&symbol, order, fill, now)?;
+                    fills += 1;
+                }
+            }
+
+            strategy.on_trade(trade)?;
+        }
+
+        Ok((count, fills))
+    }
+
+    fn match_orders(&self, codes: &[Codes]) -> Result<usize> {
+        let mut count = 0;
+        let now = *self.context().time();
+        let index = *self.offset() as i64 - 1;
+
+        for code in codes {
+            let symbol = self.context().symbols().maps().get(code).unwrap().clone();
+            // 逐笔回放的交易对由成交撮合, 这里只推进盘口
+            let orders = if self.tick_codes().contains(code) {
+                vec![]
+            } else {
+                symbol
+                    .position()
+                    .orders()
+                    .maps()
+                    .values()
+                    .filter(|order| *order.status() == OrderStatus::Pending)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+
+            let replay = self
+                .books_mut()
+                .get_mut(code)
+                .map(|replay| replay.advance(&now));
+
+            match &replay {
+                Some((prev, books)) => {
+                    let mut prev = prev.clone();
+                    for book in books {
+                        for order in &orders {
+                            if *order.status() != OrderStatus::Pending {
+                                continue;
+                            }
+                            let fill = {
+                                let mut queues = self.queues_mut();
+                                let queue = queues
+                                    .entry(order.id().clone())
+                                    .or_insert_with(|| matching::queue_ahead(order, book));
+                                matching::match_book(order, queue, prev.as_ref(), book)
+                            };
+                            if let Some(fill) = fill {
+                                self.apply_fill(&symbol, order, fill, now)?;
+                                count += 1;
+                            }
+                        }
+                        prev = Some(book.clone());
+                    }
+                    if let Some(book) = books.last() {
+                        symbol.set_book(Some(book.clone()));
+                    }
+                }
+                None => {
+                    let candle = symbol.candles().slice(index, 1);
+                    let value = |name: &str| -> Result<Option<f64>> {
+                        Ok(candle.column(name)?.f64()?.get(0))
+                    };
+                    // 缺失的k线没有成交, 跳过这一分钟的撮合
+                    let (Some(high), Some(low), Some(close)) =
+                        (value("high")?, value("low")?, value("close")?)
+                    else {
+                        continue;
+                    };
+                    for order in &orders {
+                        if let Some(fill) = matching::match_candle(order, high, low, close) {
+                            self.apply_fill(&symbol, order, fill, now)?;
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// 成交: 更新订单, 持仓, 保证金和资金
+    fn apply_fill(
+        &self,
+        symbol: &Symbol,
+        order: &Order,
+        fill: matching::Fill,
+        now: Time,
+    ) -> Result<()> {
+        let size = fill.size.min(*order.size() - *order.deal_size());
+        let deal_size = *order.deal_size() + size;
+        let deal_price = (*order.deal_price() * *order.deal_size() + fill.price * size) / deal_size;
+        let rate = if fill.maker {
+            *symbol.maker()
+        } else {
+            *symbol.taker()
+        };
+        let fee = fill.price * size * rate;
+        let frozen = frozen_margin(order);
+
+        order.set_deal_size(deal_size);
+        order.set_deal_price(deal_price);
+        *order.deal_fee_mut() += fee;
+        order.set_update_time(now);
+        if deal_size >= *order.size() {
+            order.set_status(OrderStatus::Completed);
+            self.queues_mut().shift_remove(order.id().as_str());
+        }
+        // 本次成交释放的冻结保证金
+        let frozen = frozen - frozen_margin(order);
+
+        let account = self.account(*order.code());
+        let position = symbol.position().clone();
+        let side = position.side(*order.direction());
+        let lever = *position.lever();
+        let (total, price) = (*side.size().total(), *side.price());
+
+        if order.is_open() {
+            let margin = fill.price * size / lever;
+            let price = (price * total + fill.price * size) / (total + size);
+            side.set_price(price);
+            *side.size().total_mut() += size;
+            *side.size().avail_mut() += size;
+            *position.margin().total_mut() += margin;
+            *position.margin().avail_mut() += margin;
+            *position.margin().frozen_mut() -= frozen;
+            *account.frozen_mut() -= frozen;
+            *account.avail_mut() += frozen - margin;
+        } else {
+            // 平仓按持仓均价释放保证金并结算盈亏
+            let margin = price * size / lever;
+            let pnl = match *order.direction() {
+                Direction::Long => (fill.price - price) * size,
+                Direction::Short => (price - fill.price) * size,
+            };
+            *side.size().total_mut() -= size;
+            *side.size().frozen_mut() -= size;
+            if total == size {
+                side.set_price(Decimal::ZERO);
+            }
+            *side.pnl().realized_mut() += pnl;
+            *position.pnl().realized_mut() += pnl;
+            *position.margin().total_mut() -= margin;
+            *position.margin().avail_mut() -= margin;
+            *account.total_mut() += pnl;
+            *account.avail_mut() += margin + pnl;
+        }
+        *account.total_mut() -= fee;
+        *account.avail_mut() -= fee;
+
+        let strategy = self.strategy().clone();
+        strategy.on_order()?;
+        strategy.on_position()?;
+        strategy.on_cash()?;
+
+        Ok(())
+    }
 
-            strategy.on_history_candle(*code, PyDataFrame(df.clone()))?;
+    /// 交易对所在市场的资金
+    fn account(&self, code: Codes) -> Volume {
+        match code.market() {
+            Market::Spot => self.context().spot().clone(),
+            Market::Swap => self.context().swap().clone(),
+        }
+    }
+
+    fn symbol(&self, code: Codes) -> Result<Symbol> {
+        self.context()
+            .symbols()
+            .maps()
+            .get(&code)
+            .cloned()
+            .ok_or(anyhow!("交易对不存在: {code}"))
+    }
+
+    /// 当前时间已收盘的最后一根K线的收盘价, 市价单按此价格冻结保证金
+    /// - 单步调试的两步之间偏移量已推进, 按上下文时间定位, 不读取还未收盘的K线
+    fn last_close(&self, symbol: &Symbol) -> Result<Option<Price>> {
+        let now = *self.context().time();
+        let index = (now - *self.begin()).num_minutes() + *self.history_size() as i64 - 1;
+        let Ok(index) = usize::try_from(index) else {
+            return Ok(None);
+        };
+        Ok(symbol
+            .candles()
+            .column("close")?
+            .f64()?
+            .get(index)
+            .and_then(Decimal::from_f64))
+    }
+
+    /// 推送已完结的K线, 跳过的空闲分钟的K线合并到下一次推送
+    /// - 未订阅K线事件时只追加到策略的K线, 不回调on_candle
+    fn emit_candles(&self, codes: &[Codes], notify: bool) -> Result<()> {
+        let strategy = self.strategy().clone();
+        let (from, to) = (*self.delivered(), *self.offset());
+        if to <= from {
+            return Ok(());
+        }
 
-            self.context()
+        for code in codes {
+            let candles = self
+                .context()
                 .symbols()
                 .maps()
                 .get(code)
                 .unwrap()
-                .set_candles(df);
+                .candles()
+                .slice(from as i64, to - from);
+            // 只推送已完结的K线, 缺失数据(GapPolicy::Missing)补齐的行未完结
+            let finished = candles.column("finished")?.bool()?.clone();
+            let candles = candles.filter(&finished)?;
+            if candles.height() == 0 {
+                continue;
+            }
+            strategy.on_candle(*code, Some(PyDataFrame(candles)), notify)?;
         }
+        self.set_delivered(to);
 
         Ok(())
     }
+
+    /// 空闲分钟只推进盘口, 没有挂单时不撮合, 保证策略读取的盘口是最新的
+    fn advance_books(&self, now: Time) {
+        let symbols = self.context().symbols().clone();
+        for (code, replay) in self.books_mut().iter_mut() {
+            let (_, books) = replay.advance(&now);
+            if let (Some(book), Some(symbol)) = (books.last(), symbols.maps().get(code)) {
+                symbol.set_book(Some(book.clone()));
+            }
+        }
+    }
+}
+
+/// 订单未成交部分冻结的保证金, 平仓订单不冻结保证金
+#[inline]
+fn frozen_margin(order: &Order) -> Size {
+    if order.size().is_zero() {
+        return Decimal::ZERO;
+    }
+    *order.margin() * (*order.size() - *order.deal_size()) / *order.size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pyo3::{
+        Python,
+        types::{PyAnyMethods, PyModule},
+    };
+
+    const PROBE: &std::ffi::CStr = cr#"
+class Probe:
+    def __init__(self, subscriptions):
+        self.subscriptions = subscriptions
+        self.events = []
+        self.history = 0
+        self.rows = 0
+        self.closes = []
+
+    def _subscriptions(self):
+        return self.subscriptions
+
+    def _on_inject_context(self, context):
+        self.context = context
+
+    def _on_inject_backtest(self, backtest):
+        self.backtest = backtest
+
+    def _on_init(self):
+        pass
+
+    def _on_stop(self):
+        pass
+
+    def _on_history_candle(self, code, candles):
+        self.history = candles.height
+        self.closes = candles["close"].to_list()
+
+    def _on_candle(self, code, candle, notify):
+        self.rows += candle.height
+        self.closes.extend(candle["close"].to_list())
+        if notify:
+            self.events.append("candle")
+
+    def _on_backtest_tick(self):
+        self.events.append("tick")
+
+    def _on_trade(self, trade):
+        self.events.append("trade")
+
+    def _on_timer(self, name):
+        self.events.append(name)
+
+    def _on_position(self):
+        self.events.append("position")
+
+    def _on_order(self):
+        self.events.append("order")
+
+    def _on_cash(self):
+        self.events.append("cash")
+"#;
+
+    fn count(probe: &Bound<PyAny>, event: &str) -> usize {
+        probe
+            .getattr("events")
+            .unwrap()
+            .extract::<Vec<String>>()
+            .unwrap()
+            .iter()
+            .filter(|item| *item == event)
+            .count()
+    }
+
+    fn probe<'py>(py: Python<'py>, subscriptions: &[&str]) -> Bound<'py, PyAny> {
+        PyModule::from_code(py, PROBE, c"probe.py", c"probe")
+            .unwrap()
+            .getattr("Probe")
+            .unwrap()
+            .call1((subscriptions.to_vec(),))
+            .unwrap()
+    }
+
+    /// 离线的一小时回测, 数据和结果写入临时目录
+    fn fixture(probe: &Bound<PyAny>, dir: &std::path::Path, skip_idle: bool) -> Result<Backtest> {
+        Backtest::new(
+            probe,
+            "2024-01-01 00:00",
+            "2024-01-01 01:00",
+            vec![(Codes::BTC_SWAP, dec!(0.0005), dec!(0.0002), dec!(1))],
+            dec!(1000),
+            dec!(1000),
+            60,
+            false,
+            GapPolicy::Fill,
+            dir.join("data").to_str(),
+            DataFormat::Ipc,
+            DataSource::Fixture,
+            None,
+            1,
+            0,
+            1,
+            false,
+            "UTC",
+            false,
+            false,
+            skip_idle,
+            None,
+            "1d",
+            true,
+            Some(7),
+            None,
+            dir.join("results").to_str(),
+            false,
+        )
+    }
+
+    fn start(backtest: &Backtest) {
+        let codes = backtest.sync_data().unwrap();
+        backtest
+            .prepare(&codes, &backtest.load_frames(&codes).unwrap())
+            .unwrap();
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("fuxi-backtest-{}", crate::helpers::id::new()))
+    }
+
+    #[test]
+    fn unsubscribed_candles_are_appended() {
+        pyo3::prepare_freethreaded_python();
+        let dir = temp_dir();
+
+        Python::with_gil(|py| {
+            let probe = probe(py, &["signal"]);
+            let backtest = fixture(&probe, &dir, false).unwrap();
+            let codes = backtest.sync_data().unwrap();
+            backtest
+                .prepare(&codes, &backtest.load_frames(&codes).unwrap())
+                .unwrap();
+            while backtest.step().unwrap().is_some() {}
+
+            // 没有重写on_candle也能取到回测期间完结的K线
+            assert_eq!(
+                probe.getattr("rows").unwrap().extract::<usize>().unwrap(),
+                60
+            );
+            assert_eq!(count(&probe, "candle"), 0);
+            assert_eq!(count(&probe, "tick"), 61);
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn resumed_run_matches_straight_run() {
+        pyo3::prepare_freethreaded_python();
+        let dir = temp_dir();
+        let path = dir.join("checkpoint.json");
+        let split = chrono_tz::UTC
+            .with_ymd_and_hms(2024, 1, 1, 0, 30, 0)
+            .unwrap();
+        let closes = |probe: &Bound<PyAny>| {
+            probe
+                .getattr("closes")
+                .unwrap()
+                .extract::<Vec<f64>>()
+                .unwrap()
+        };
+
+        Python::with_gil(|py| {
+            let straight_probe = probe(py, &["candle"]);
+            let straight = fixture(&straight_probe, &dir, false).unwrap();
+            start(&straight);
+            straight.step().unwrap();
+            straight
+                .place_order(
+                    Codes::BTC_SWAP,
+                    Method::Market,
+                    Direction::Long,
+                    Side::Buy,
+                    dec!(1),
+                    Decimal::ZERO,
+                    None,
+                )
+                .unwrap();
+            while (*straight.cursor()).is_some_and(|time| time < split) {
+                straight.step().unwrap();
+            }
+            straight.save_checkpoint(&path).unwrap();
+            while straight.step().unwrap().is_some() {}
+
+            let resumed_probe = probe(py, &["candle"]);
+            let resumed = fixture(&resumed_probe, &dir, false).unwrap();
+            resumed
+                .restore(crate::checkpoint::Checkpoint::load(&path).unwrap())
+                .unwrap();
+            start(&resumed);
+            while resumed.step().unwrap().is_some() {}
+
+            assert_eq!(*resumed.equity(), *straight.equity());
+            let long = |backtest: &Backtest| {
+                *backtest
+                    .symbol(Codes::BTC_SWAP)
+                    .unwrap()
+                    .position()
+                    .long()
+                    .size()
+                    .total()
+            };
+            assert_eq!(long(&resumed), dec!(1));
+            assert_eq!(long(&resumed), long(&straight));
+
+            // 恢复后策略的K线窗口与不中断时一致: 历史K线截止到检查点前一分钟
+            let (straight, resumed) = (closes(&straight_probe), closes(&resumed_probe));
+            assert_eq!(straight.len(), 120);
+            assert_eq!(resumed.len(), 91);
+            assert_eq!(resumed[..], straight[29..]);
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn skip_idle_rejects_minutely_timer() {
+        pyo3::prepare_freethreaded_python();
+        let dir = temp_dir();
+
+        Python::with_gil(|py| {
+            assert!(fixture(&probe(py, &["timer"]), &dir, true).is_err());
+            assert!(fixture(&probe(py, &["timer"]), &dir, false).is_ok());
+            assert!(fixture(&probe(py, &["signal"]), &dir, true).is_ok());
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fixture_backtest_fills_and_cancels() {
+        pyo3::prepare_freethreaded_python();
+        let dir = temp_dir();
+
+        Python::with_gil(|py| {
+            let probe = probe(py, &["candle", "order", "position", "cash"]);
+            let backtest = fixture(&probe, &dir, false).unwrap();
+
+            let codes = backtest.sync_data().unwrap();
+            assert_eq!(codes, vec![Codes::BTC_SWAP]);
+            backtest
+                .prepare(&codes, &backtest.load_frames(&codes).unwrap())
+                .unwrap();
+            assert_eq!(
+                probe
+                    .getattr("history")
+                    .unwrap()
+                    .extract::<usize>()
+                    .unwrap(),
+                60
+            );
+
+            // 开始时间的K线在下一分钟完结, 第一个事件在00:01
+            assert_eq!(backtest.step().unwrap(), Some(0));
+            assert_eq!(backtest.equity().len(), 2);
+            assert_eq!(count(&probe, "candle"), 1);
+
+            let swap = backtest.context().swap().clone();
+            let symbol = backtest.symbol(Codes::BTC_SWAP).unwrap();
+            let bar_close = |index: usize| {
+                symbol
+                    .candles()
+                    .column("close")
+                    .unwrap()
+                    .f64()
+                    .unwrap()
+                    .get(index)
+                    .and_then(Decimal::from_f64)
+                    .unwrap()
+            };
+            // 两步之间按当前时间已收盘的K线计价, 不读取还未收盘的K线
+            let close = backtest.last_close(&symbol).unwrap().unwrap();
+            assert_eq!(close, bar_close(60));
+
+            // 资金不足的开仓被拒绝, 不冻结资金
+            let rejected = backtest
+                .place_order(
+                    Codes::BTC_SWAP,
+                    Method::Market,
+                    Direction::Long,
+                    Side::Buy,
+                    dec!(100),
+                    Decimal::ZERO,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(*rejected.status(), OrderStatus::Rejected);
+            assert_eq!(*swap.avail(), dec!(1000));
+
+            let order = backtest
+                .place_order(
+                    Codes::BTC_SWAP,
+                    Method::Market,
+                    Direction::Long,
+                    Side::Buy,
+                    dec!(1),
+                    Decimal::ZERO,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(*order.status(), OrderStatus::Pending);
+            assert_eq!(*order.margin(), close);
+            assert_eq!(*swap.frozen(), close);
+            assert_eq!(*swap.avail(), dec!(1000) - close);
+
+            // 市价单按下一分钟收盘的K线成交, 冻结和实际保证金的差额退回
+            assert_eq!(backtest.step().unwrap(), Some(1));
+            assert_eq!(*order.status(), OrderStatus::Completed);
+            let price = bar_close(61);
+            assert_eq!(*order.deal_price(), price);
+            let fee = price * dec!(0.0005);
+            assert_eq!(*order.deal_fee(), fee);
+
+            let position = symbol.position().clone();
+            assert_eq!(*position.long().size().total(), dec!(1));
+            assert_eq!(*position.long().price(), price);
+            assert_eq!(*position.margin().total(), price);
+            assert_eq!(*swap.frozen(), Decimal::ZERO);
+            assert_eq!(*swap.total(), dec!(1000) - fee);
+            assert_eq!(*swap.avail(), dec!(1000) - price - fee);
+
+            // 平仓挂单冻结持仓, 取消后释放
+            let close_order = backtest
+                .place_order(
+                    Codes::BTC_SWAP,
+                    Method::Limit,
+                    Direction::Long,
+                    Side::Sell,
+                    dec!(1),
+                    dec!(1000000),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(*position.long().size().avail(), Decimal::ZERO);
+            assert_eq!(*position.long().size().frozen(), dec!(1));
+            assert!(backtest.set_lever(Codes::BTC_SWAP, dec!(2)).is_err());
+
+            backtest
+                .cancel_order(Codes::BTC_SWAP, close_order.id())
+                .unwrap();
+            assert_eq!(*close_order.status(), OrderStatus::Cancelled);
+            assert_eq!(*position.long().size().avail(), dec!(1));
+            assert_eq!(*position.long().size().frozen(), Decimal::ZERO);
+            assert!(
+                backtest
+                    .cancel_order(Codes::BTC_SWAP, close_order.id())
+                    .is_err()
+            );
+
+            while backtest.step().unwrap().is_some() {}
+
+            // 每分钟记录一次权益, 包括结束时间
+            assert_eq!(backtest.equity().len(), 61);
+            assert_eq!(count(&probe, "candle"), 60);
+            assert_eq!(count(&probe, "order"), 5);
+            let metrics = backtest.metrics();
+            assert_eq!((metrics.orders, metrics.fills), (3, 1));
+            assert_eq!(metrics.fees, fee.to_f64().unwrap());
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn same_seed_reproduces_orders() {
+        pyo3::prepare_freethreaded_python();
+        let run = |py: Python| {
+            let dir = temp_dir();
+            let backtest = fixture(&probe(py, &["candle"]), &dir, false).unwrap();
+            start(&backtest);
+            backtest.step().unwrap();
+            for (method, side, price) in [
+                (Method::Market, Side::Buy, Decimal::ZERO),
+                (Method::Limit, Side::Sell, dec!(1000000)),
+            ] {
+                backtest
+                    .place_order(
+                        Codes::BTC_SWAP,
+                        method,
+                        Direction::Long,
+                        side,
+                        dec!(1),
+                        price,
+                        None,
+                    )
+                    .unwrap();
+                backtest.step().unwrap();
+            }
+            while backtest.step().unwrap().is_some() {}
+            let _ = std::fs::remove_dir_all(&dir);
+
+            let orders = serde_json::to_string(&backtest.orders()).unwrap();
+            (orders, backtest.equity().clone())
+        };
+
+        Python::with_gil(|py| assert_eq!(run(py), run(py)));
+    }
 }
@@ -0,0 +1,187 @@
+use crate::{
+    backtest::Backtest,
+    types::{
+        alias::{Map, Time},
+        base::{Codes, LogLevel, Volume},
+        position::Position,
+    },
+};
+use anyhow::{Result, anyhow, ensure};
+use chrono::Duration;
+use pyo3::pymethods;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::Write, path::Path};
+
+/// 回测检查点, 记录一分钟回放结束时的引擎状态和随机数序列的位置
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// 下一个待回放的分钟
+    #[serde(with = "crate::helpers::time::serde_time")]
+    pub time: Time,
+    pub offset: usize,
+    pub spot: Volume,
+    pub swap: Volume,
+    pub positions: Map<Codes, Position>,
+    pub queues: Map<String, f64>,
+    pub equity: Vec<(i64, f64)>,
+    pub seed: u64,
+    pub rng_position: u128,
+}
+
+impl Checkpoint {
+    /// 先写临时文件再重命名, 中断时不会留下不完整的检查点
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&serde_json::to_vec(self)?)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|err| anyhow!("读取检查点失败: {path:?} {err}"))?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    /// 恢复随机数序列的位置, 之后的随机结果与不中断的回测一致
+    pub fn rng(&self) -> ChaCha12Rng {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+        rng.set_word_pos(self.rng_position);
+        rng
+    }
+}
+
+#[pymethods]
+impl Backtest {
+    /// 保存检查点
+    #[pyo3(name = "save_checkpoint")]
+    fn py_save_checkpoint(&self, path: &str) -> Result<()> {
+        self.save_checkpoint(Path::new(path))
+    }
+
+    /// 从检查点恢复, 需要在启动回测前调用
+    #[pyo3(name = "load_checkpoint")]
+    fn py_load_checkpoint(&self, path: &str) -> Result<()> {
+        self.restore(Checkpoint::load(Path::new(path))?)
+    }
+}
+
+impl Backtest {
+    pub fn save_checkpoint(&self, path: &Path) -> Result<()> {
+        let context = self.context().clone();
+        let time = (*self.cursor()).unwrap_or(*self.begin());
+        let checkpoint = Checkpoint {
+            time,
+            offset: *self.offset(),
+            spot: context.spot().clone(),
+            swap: context.swap().clone(),
+            positions: context
+                .symbols()
+                .maps()
+                .iter()
+                .map(|(code, symbol)| (*code, symbol.position().clone()))
+                .collect(),
+            queues: self.queues().clone(),
+            equity: self.equity().clone(),
+            seed: self.config().seed,
+            rng_position: self.rng().get_word_pos(),
+        };
+        checkpoint.save(path)?;
+
+        context.show_log(
+            LogLevel::Debug,
+            format_args!("保存检查点 路径: {}", path.display()),
+        );
+        Ok(())
+    }
+
+    /// 恢复引擎状态, 回放从检查点记录的分钟继续
+    pub fn restore(&self, checkpoint: Checkpoint) -> Result<()> {
+        let time = checkpoint.time.with_timezone(&*self.context().tz());
+        ensure!(
+            time >= *self.begin() && time <= *self.end() + Duration::minutes(1),
+            "检查点时间不在回测区间内: {time}"
+        );
+        // 数据偏移量由时间推出, 检查点中的值只用于校验
+        let offset = *self.history_size() + (time - *self.begin()).num_minutes() as usize;
+        ensure!(
+            checkpoint.offset == offset,
+            "检查点与回测区间不一致: 数据偏移量={}, 预期={offset}",
+            checkpoint.offset
+        );
+
+        let context = self.context().clone();
+        for (code, position) in checkpoint.positions {
+            let symbol = context
+                .symbols()
+                .maps()
+                .get(&code)
+                .cloned()
+                .ok_or(anyhow!("检查点中的交易对不存在: {code}"))?;
+            symbol.set_position(position);
+        }
+        context.set_spot(checkpoint.spot);
+        context.set_swap(checkpoint.swap);
+        if time > *self.begin() {
+            context.set_time(time - Duration::minutes(1));
+        }
+
+        self.set_offset(offset);
+        self.set_queues(checkpoint.queues);
+        self.set_equity(checkpoint.equity);
+        self.set_rng(checkpoint.rng());
+        self.config_mut().seed = checkpoint.seed;
+        self.set_resume(Some(time));
+
+        context.show_log(LogLevel::Info, format_args!("从检查点恢复 时间: {time}"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::constants::DEFAULT_TZ;
+    use chrono::TimeZone;
+    use rand::Rng;
+
+    #[test]
+    fn save_load_round_trip() {
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        let _ = rng.random::<u64>();
+        let checkpoint = Checkpoint {
+            time: DEFAULT_TZ.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            offset: 5000 + 24 * 60,
+            spot: Default::default(),
+            swap: Default::default(),
+            positions: Default::default(),
+            queues: [("order".to_string(), 1.5)].into_iter().collect(),
+            equity: vec![(1, 1000.0), (2, 1001.0)],
+            seed: 7,
+            rng_position: rng.get_word_pos(),
+        };
+
+        let path = std::env::temp_dir().join(format!(
+            "fuxi-checkpoint-{}.json",
+            crate::helpers::id::new()
+        ));
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.time, checkpoint.time);
+        assert_eq!(loaded.offset, checkpoint.offset);
+        assert_eq!(loaded.queues, checkpoint.queues);
+        assert_eq!(loaded.equity, checkpoint.equity);
+        // 恢复后的随机数与不中断时一致
+        assert_eq!(loaded.rng().random::<u64>(), rng.random::<u64>());
+    }
+}
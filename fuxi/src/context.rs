@@ -1,5 +1,7 @@
 use crate::{
+    helpers::{constants::DEFAULT_TZ, log::LogFile},
     runtime::Runtime,
+    scheduler::{Cron, Schedule, Scheduler, TimerSpec},
     types::{
         alias::{Price, Size, Time, default_time},
         base::{Codes, Direction, LogLevel, Method, Mode, Side, Volume},
@@ -7,7 +9,8 @@ use crate::{
         order::Order,
     },
 };
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chrono_tz::Tz;
 use fuxi_macros::model;
 use pyo3::{
     Bound, pymethods,
@@ -18,24 +21,31 @@ use std::{fmt::Arguments, sync::Arc};
 #[model(python)]
 pub struct Context {
     pub mode: Mode,
+    pub tz: Tz,
     pub time: Time,
     pub spot: Volume,
     pub swap: Volume,
     pub symbols: SymbolMap,
+    scheduler: Scheduler,
     runtime: Option<Arc<dyn Runtime>>,
     log_level: (LogLevel, LogLevel),
+    /// 输出的日志写入文件, 用于保存回测结果
+    logs: Option<LogFile>,
 }
 
 impl Default for Context {
     fn default() -> Self {
         Self::from(ContextData {
             mode: Mode::Backtest,
+            tz: DEFAULT_TZ,
             runtime: None,
             log_level: (LogLevel::Info, LogLevel::Info),
+            logs: None,
             time: default_time(),
             spot: Default::default(),
             swap: Default::default(),
             symbols: Default::default(),
+            scheduler: Default::default(),
         })
     }
 }
@@ -51,8 +61,8 @@ impl Context {
             return;
         }
 
-        crate::helpers::log::print(format_args!(
-            "{} {}{}{} - {}\n",
+        let line = format!(
+            "{} {}{}{} - {}",
             self.time().format(crate::helpers::constants::FMT_S),
             match *self.mode() {
                 Mode::Backtest => "📊",
@@ -68,7 +78,11 @@ impl Context {
                 LogLevel::Error => "💥",
             },
             msg,
-        ));
+        );
+        crate::helpers::log::print(format_args!("{line}\n"));
+        if let Some(logs) = self.logs_mut().as_mut() {
+            let _ = logs.write(&line);
+        }
     }
 
     #[inline]
@@ -99,22 +113,19 @@ impl Context {
         self.set_log_level((engine, strategy));
     }
 
-    #[staticmethod]
     #[pyo3(signature = (millis))]
-    fn millis_to_time(millis: i64) -> Result<Time> {
-        crate::helpers::time::millis_to_time(millis)
+    fn millis_to_time(&self, millis: i64) -> Result<Time> {
+        crate::helpers::time::millis_to_time(millis, &self.tz())
     }
 
-    #[staticmethod]
     #[pyo3(signature = (nanos))]
-    fn nanos_to_time(nanos: i64) -> Time {
-        crate::helpers::time::nanos_to_time(nanos)
+    fn nanos_to_time(&self, nanos: i64) -> Time {
+        crate::helpers::time::nanos_to_time(nanos, &self.tz())
     }
 
-    #[staticmethod]
     #[pyo3(signature = (s))]
-    fn str_to_time(s: &str) -> Result<Time> {
-        crate::helpers::time::str_to_time(s)
+    fn str_to_time(&self, s: &str) -> Result<Time> {
+        crate::helpers::time::str_to_time(s, &self.tz())
     }
 
     #[staticmethod]
@@ -128,6 +139,36 @@ impl Context {
         crate::helpers::id::new()
     }
 
+    #[pyo3(signature = (name, every=None, cron=None, offset=None, tz=None))]
+    fn add_timer(
+        &self,
+        name: &str,
+        every: Option<&str>,
+        cron: Option<&str>,
+        offset: Option<&str>,
+        tz: Option<&str>,
+    ) -> Result<()> {
+        let schedule = match (every, cron) {
+            (Some(every), None) => Schedule::every(every)?,
+            (None, Some(cron)) => Schedule::Cron(Cron::parse(cron)?),
+            _ => bail!("定时器需要且只能指定every或cron之一: {name}"),
+        };
+        self.scheduler().add(TimerSpec {
+            name: name.to_string(),
+            schedule,
+            offset: match offset {
+                Some(offset) => crate::helpers::time::parse_duration(offset)?,
+                None => chrono::Duration::zero(),
+            },
+            tz: tz.map(crate::helpers::time::parse_tz).transpose()?,
+        })
+    }
+
+    #[pyo3(signature = (name))]
+    fn remove_timer(&self, name: &str) -> bool {
+        self.scheduler().remove(name)
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[pyo3(signature = (code, method, direction, side, size, price, remark=None))]
     fn place_order(
@@ -149,4 +190,10 @@ impl Context {
         let runtime = self.runtime().clone().unwrap();
         runtime.cancel_order(code, id)
     }
+
+    #[pyo3(signature = (code, lever))]
+    fn set_lever(&self, code: Codes, lever: Size) -> Result<()> {
+        let runtime = self.runtime().clone().unwrap();
+        runtime.set_lever(code, lever)
+    }
 }
@@ -0,0 +1,923 @@
+use crate::{
+    helpers::constants::{DATA_DIR_ENV, DEFAULT_TZ},
+    providers::{normalize, normalize_time},
+    types::{
+        alias::Time,
+        base::{Codes, DataFormat, Interval, Market, Side},
+        market::{OrderBook, Trade, TradeData},
+    },
+};
+use anyhow::{Result, anyhow, ensure};
+use chrono_tz::Tz;
+use fuxi_macros::model;
+use polars::prelude::*;
+use pyo3::pymethods;
+use pyo3_polars::PyDataFrame;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+use strum::IntoEnumIterator;
+
+pub const CANDLE_COLUMNS: [&str; 7] =
+    ["time", "open", "high", "low", "close", "volume", "finished"];
+
+pub const TICK_COLUMNS: [&str; 4] = ["time", "side", "price", "size"];
+
+const ROW_GROUP_SIZE: usize = 30 * 24 * 60;
+
+pub fn scan_file(path: &Path) -> Result<LazyFrame> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let source = PlPathRef::from_local_path(path).into_owned();
+    Ok(match (ext.as_str(), DataFormat::from_extension(&ext)) {
+        (_, Some(DataFormat::Ipc)) => LazyFrame::scan_ipc(source, Default::default())?,
+        (_, Some(DataFormat::Parquet)) => LazyFrame::scan_parquet(source, Default::default())?,
+        ("csv", _) => LazyCsvReader::new(source)
+            .with_has_header(true)
+            .with_try_parse_dates(true)
+            .finish()?,
+        _ => return Err(anyhow!("不支持的文件格式: {}", path.display())),
+    })
+}
+
+#[model(python)]
+pub struct DataStore {
+    dir: PathBuf,
+    format: DataFormat,
+    tz: Tz,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct DataMeta {
+    pub code: Codes,
+    pub interval: Interval,
+    pub path: PathBuf,
+    pub rows: usize,
+    pub begin: Option<Time>,
+    pub end: Option<Time>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DataStats {
+    pub rows: usize,
+    pub begin: Option<i64>,
+    pub end: Option<i64>,
+    pub gaps: usize,
+    pub missing: usize,
+    pub duplicates: usize,
+    pub unordered: usize,
+}
+
+impl DataStats {
+    pub fn from_times(times: &[i64], step: Option<i64>) -> Self {
+        let unordered = times.windows(2).filter(|w| w[1] < w[0]).count();
+
+        let mut sorted = times.to_vec();
+        sorted.sort_unstable();
+        let duplicates = sorted.windows(2).filter(|w| w[0] == w[1]).count();
+        sorted.dedup();
+
+        let (gaps, missing) = match step {
+            Some(step) if step > 0 => sorted
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .filter(|diff| *diff > step)
+                .fold((0, 0), |(gaps, missing), diff| {
+                    (gaps + 1, missing + (diff / step - 1) as usize)
+                }),
+            _ => (0, 0),
+        };
+
+        Self {
+            rows: times.len(),
+            begin: sorted.first().copied(),
+            end: sorted.last().copied(),
+            gaps,
+            missing,
+            duplicates,
+            unordered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub code: Codes,
+    pub interval: Interval,
+    pub rows: usize,
+    pub begin: Option<i64>,
+    pub end: Option<i64>,
+    pub size: u64,
+    pub checksum: String,
+}
+
+impl DataStore {
+    pub fn new(dir: Option<&str>, format: DataFormat, tz: Tz) -> Result<Self> {
+        let dir = match dir {
+            Some(dir) => PathBuf::from(dir),
+            None => match std::env::var(DATA_DIR_ENV) {
+                Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+                _ => PathBuf::from("data"),
+            },
+        };
+        let dir = if dir.is_absolute() {
+            dir
+        } else {
+            std::env::current_dir()?.join(dir)
+        };
+        Ok(Self::from(DataStoreData { dir, format, tz }))
+    }
+
+    #[inline]
+    pub fn time_type(&self) -> DataType {
+        DataType::Datetime(
+            TimeUnit::Nanoseconds,
+            Some(TimeZone::from_chrono(&self.tz())),
+        )
+    }
+
+    #[inline]
+    pub fn market_dir(&self, market: Market) -> PathBuf {
+        self.dir().join(match market {
+            Market::Spot => "spot",
+            Market::Swap => "swap",
+        })
+    }
+
+    #[inline]
+    pub fn file_stem(code: Codes) -> String {
+        code.code().replace("/", "_").replace(":", "_")
+    }
+
+    #[inline]
+    fn format_path(&self, code: Codes, interval: Interval, ext: &str) -> PathBuf {
+        self.market_dir(code.market()).join(format!(
+            "{}-{}.{ext}",
+            Self::file_stem(code),
+            interval.abbr()
+        ))
+    }
+
+    #[inline]
+    pub fn path(&self, code: Codes, interval: Interval) -> PathBuf {
+        self.format_path(code, interval, self.format().extension())
+    }
+
+    #[inline]
+    pub fn manifest_path(&self, code: Codes, interval: Interval) -> PathBuf {
+        self.format_path(code, interval, "json")
+    }
+
+    #[inline]
+    fn tick_format_path(&self, code: Codes, kind: &str, ext: &str) -> PathBuf {
+        self.market_dir(code.market())
+            .join(format!("{}-{kind}.{ext}", Self::file_stem(code)))
+    }
+
+    /// 逐笔数据文件路径, [`kind`]为book或trades
+    #[inline]
+    pub fn tick_path(&self, code: Codes, kind: &str) -> PathBuf {
+        self.tick_format_path(code, kind, self.format().extension())
+    }
+
+    pub fn find_tick(&self, code: Codes, kind: &str) -> Option<PathBuf> {
+        DataFormat::iter()
+            .map(|item| self.tick_format_path(code, kind, item.extension()))
+            .find(|path| path.exists())
+    }
+
+    pub fn find(&self, code: Codes, interval: Interval) -> Option<PathBuf> {
+        let format = *self.format();
+        std::iter::once(format)
+            .chain(DataFormat::iter().filter(|item| *item != format))
+            .map(|item| self.format_path(code, interval, item.extension()))
+            .find(|path| path.exists())
+    }
+
+    #[inline]
+    pub fn exists(&self, code: Codes, interval: Interval) -> bool {
+        self.find(code, interval).is_some()
+    }
+
+    pub fn prepare(&self) -> Result<()> {
+        for market in Market::iter() {
+            let dir = self.market_dir(market);
+            std::fs::create_dir_all(&dir)?;
+
+            // 兼容旧布局: <code>.feather(IPC)按配置的格式重写为<code>-1m, 同时生成数据清单
+            for code in Codes::iter().filter(|code| code.market() == market) {
+                let legacy = dir.join(format!("{}.feather", Self::file_stem(code)));
+                if legacy.exists() && !self.exists(code, Interval::Min) {
+                    let mut df = normalize(scan_file(&legacy)?, &self.tz())?;
+                    self.write(code, Interval::Min, &mut df)?;
+                    std::fs::remove_file(&legacy)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn list(&self, market: Market) -> Result<Vec<(Codes, Interval)>> {
+        let dir = self.market_dir(market);
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let stems = Codes::iter()
+            .filter(|code| code.market() == market)
+            .map(|code| (Self::file_stem(code), code))
+            .collect::<HashMap<_, _>>();
+
+        let mut items = vec![];
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(DataFormat::from_extension)
+                .is_none()
+            {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some((stem, interval)) = name.rsplit_once("-") else {
+                continue;
+            };
+            if let (Some(code), Some(interval)) = (stems.get(stem), Interval::from_abbr(interval)) {
+                items.push((*code, interval));
+            }
+        }
+        items.sort();
+        items.dedup();
+
+        Ok(items)
+    }
+
+    /// 先按存储的时间列过滤, 便于下推到文件读取, 再转换到当前时区
+    fn scan_range(&self, path: &Path, begin: Option<Time>, end: Option<Time>) -> Result<LazyFrame> {
+        let mut df = scan_file(path)?;
+        let dtype = df
+            .collect_schema()?
+            .get("time")
+            .cloned()
+            .ok_or(anyhow!("缺少时间列: {}", path.display()))?;
+        let time_lit = |time: Time| lit(time.naive_utc()).cast(dtype.clone());
+
+        if let Some(begin) = begin {
+            df = df.filter(col("time").gt_eq(time_lit(begin)));
+        }
+        if let Some(end) = end {
+            df = df.filter(col("time").lt_eq(time_lit(end)));
+        }
+
+        Ok(df.with_column(
+            col("time")
+                .dt()
+                .convert_time_zone(TimeZone::from_chrono(&self.tz())),
+        ))
+    }
+
+    pub fn scan(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Option<Time>,
+        end: Option<Time>,
+    ) -> Result<LazyFrame> {
+        let path = self.find(code, interval).ok_or(anyhow!(
+            "数据文件不存在: {}",
+            self.path(code, interval).display()
+        ))?;
+        self.scan_range(&path, begin, end)
+    }
+
+    pub fn meta(&self, code: Codes, interval: Interval) -> Result<Option<DataMeta>> {
+        let Some(path) = self.find(code, interval) else {
+            return Ok(None);
+        };
+
+        let df = self
+            .scan(code, interval, None, None)?
+            .select([
+                len().alias("rows"),
+                col("time").min().alias("begin"),
+                col("time").max().alias("end"),
+            ])
+            .collect()?;
+
+        let rows = df
+            .column("rows")?
+            .cast(&DataType::UInt64)?
+            .u64()?
+            .get(0)
+            .unwrap_or_default() as usize;
+        let begin = df.column("begin")?.cast(&DataType::Int64)?.i64()?.get(0);
+        let end = df.column("end")?.cast(&DataType::Int64)?.i64()?.get(0);
+
+        Ok(Some(DataMeta {
+            code,
+            interval,
+            path,
+            rows,
+            begin: begin.map(|begin| crate::helpers::time::nanos_to_time(begin, &self.tz())),
+            end: end.map(|end| crate::helpers::time::nanos_to_time(end, &self.tz())),
+        }))
+    }
+
+    pub fn validate(df: &DataFrame) -> Result<()> {
+        let schema = df.schema();
+        for name in CANDLE_COLUMNS {
+            ensure!(schema.contains(name), "缺少数据列: {name}");
+        }
+        ensure!(
+            matches!(
+                schema.get("time"),
+                Some(DataType::Datetime(TimeUnit::Nanoseconds, Some(_)))
+            ),
+            "时间列类型错误: {:?}",
+            schema.get("time")
+        );
+        for name in &CANDLE_COLUMNS[1..6] {
+            ensure!(
+                matches!(schema.get(name), Some(DataType::Float64)),
+                "数据列类型错误: {name}={:?}",
+                schema.get(name)
+            );
+        }
+        ensure!(
+            matches!(schema.get("finished"), Some(DataType::Boolean)),
+            "数据列类型错误: finished={:?}",
+            schema.get("finished")
+        );
+        Ok(())
+    }
+
+    fn write_file(&self, path: &Path, df: &mut DataFrame) -> Result<()> {
+        let format = *self.format();
+        let tmp_path = path.with_extension(format!("{}.tmp", format.extension()));
+        {
+            let mut file = File::create(&tmp_path)?;
+            match format {
+                DataFormat::Ipc => IpcWriter::new(&mut file).finish(df)?,
+                DataFormat::Parquet => {
+                    ParquetWriter::new(&mut file)
+                        .with_compression(ParquetCompression::Zstd(None))
+                        .with_statistics(StatisticsOptions::full())
+                        .with_row_group_size(Some(ROW_GROUP_SIZE))
+                        .finish(df)?;
+                }
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    pub fn write(&self, code: Codes, interval: Interval, df: &mut DataFrame) -> Result<Manifest> {
+        Self::validate(df)?;
+
+        let format = *self.format();
+        let path = self.path(code, interval);
+        self.write_file(&path, df)?;
+
+        let (size, checksum) = Self::checksum(&path)?;
+        let times = df.column("time")?.cast(&DataType::Int64)?;
+        let times = times.i64()?;
+        let manifest = Manifest {
+            code,
+            interval,
+            rows: df.height(),
+            begin: times.min(),
+            end: times.max(),
+            size,
+            checksum,
+        };
+
+        for item in DataFormat::iter().filter(|item| *item != format) {
+            let path = self.format_path(code, interval, item.extension());
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        let manifest_path = self.manifest_path(code, interval);
+        let tmp_path = manifest_path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&manifest)?)?;
+        std::fs::rename(&tmp_path, &manifest_path)?;
+
+        Ok(manifest)
+    }
+
+    pub fn verify(&self, code: Codes, interval: Interval) -> Result<Manifest> {
+        let path = self.find(code, interval).ok_or(anyhow!(
+            "数据文件不存在: {}",
+            self.path(code, interval).display()
+        ))?;
+
+        let manifest_path = self.manifest_path(code, interval);
+        let manifest = serde_json::from_slice::<Manifest>(
+            &std::fs::read(&manifest_path)
+                .map_err(|err| anyhow!("读取数据清单失败: {} {err}", manifest_path.display()))?,
+        )?;
+
+        let (size, checksum) = Self::checksum(&path)?;
+        ensure!(
+            size == manifest.size && checksum == manifest.checksum,
+            "数据文件校验失败: {}, 大小: {size}/{}, 校验和: {checksum}/{}",
+            path.display(),
+            manifest.size,
+            manifest.checksum,
+        );
+
+        Ok(manifest)
+    }
+
+    fn checksum(path: &Path) -> Result<(u64, String)> {
+        let mut file = File::open(path)?;
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut file, &mut hasher)?;
+        Ok((size, hex::encode(hasher.finalize())))
+    }
+
+    pub fn import(&self, code: Codes, interval: Interval, path: &Path) -> Result<Manifest> {
+        self.prepare()?;
+        let mut df = normalize(scan_file(path)?, &self.tz())?;
+        self.write(code, interval, &mut df)
+    }
+
+    pub fn stats(&self, code: Codes, interval: Interval) -> Result<DataStats> {
+        let df = self
+            .scan(code, interval, None, None)?
+            .select([col("time")])
+            .collect()?;
+        let times = df.column("time")?.cast(&DataType::Int64)?;
+        let times = times.i64()?.into_no_null_iter().collect::<Vec<_>>();
+        Ok(DataStats::from_times(
+            &times,
+            interval.minutes().map(|minutes| minutes * 60_000_000_000),
+        ))
+    }
+
+    pub fn check(&self, code: Codes, interval: Interval) -> Result<Vec<(&'static str, usize)>> {
+        let checksum = match self.verify(code, interval) {
+            Ok(_) => 0,
+            Err(_) => 1,
+        };
+        let stats = self.stats(code, interval)?;
+
+        let ohlc = col("high")
+            .lt(col("low"))
+            .or(col("high").lt(col("open")))
+            .or(col("high").lt(col("close")))
+            .or(col("low").gt(col("open")))
+            .or(col("low").gt(col("close")));
+        let non_positive = col("open")
+            .lt_eq(lit(0.0))
+            .or(col("high").lt_eq(lit(0.0)))
+            .or(col("low").lt_eq(lit(0.0)))
+            .or(col("close").lt_eq(lit(0.0)));
+        let df = self
+            .scan(code, interval, None, None)?
+            .select([
+                ohlc.cast(DataType::UInt64).sum().alias("ohlc"),
+                non_positive
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias("non_positive"),
+            ])
+            .collect()?;
+        let first = |name: &str| -> Result<usize> {
+            Ok(df
+                .column(name)?
+                .cast(&DataType::UInt64)?
+                .u64()?
+                .get(0)
+                .unwrap_or_default() as usize)
+        };
+
+        Ok(vec![
+            ("checksum", checksum),
+            ("unordered", stats.unordered),
+            ("duplicates", stats.duplicates),
+            ("ohlc", first("ohlc")?),
+            ("non_positive", first("non_positive")?),
+            ("gaps", stats.gaps),
+        ])
+    }
+
+    pub fn validate_tick(df: &DataFrame) -> Result<()> {
+        let schema = df.schema();
+        for name in TICK_COLUMNS {
+            ensure!(schema.contains(name), "缺少数据列: {name}");
+        }
+        ensure!(
+            matches!(
+                schema.get("time"),
+                Some(DataType::Datetime(TimeUnit::Nanoseconds, Some(_)))
+            ),
+            "时间列类型错误: {:?}",
+            schema.get("time")
+        );
+        ensure!(
+            matches!(schema.get("side"), Some(DataType::String)),
+            "数据列类型错误: side={:?}",
+            schema.get("side")
+        );
+        for name in &TICK_COLUMNS[2..] {
+            ensure!(
+                matches!(schema.get(name), Some(DataType::Float64)),
+                "数据列类型错误: {name}={:?}",
+                schema.get(name)
+            );
+        }
+        Ok(())
+    }
+
+    fn write_tick(&self, code: Codes, kind: &str, df: &mut DataFrame) -> Result<()> {
+        let format = *self.format();
+        self.write_file(&self.tick_path(code, kind), df)?;
+        for item in DataFormat::iter().filter(|item| *item != format) {
+            let path = self.tick_format_path(code, kind, item.extension());
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn import_tick(&self, code: Codes, kind: &str, sides: [&str; 2], path: &Path) -> Result<usize> {
+        self.prepare()?;
+        let mut df = normalize_time(scan_file(path)?, &self.tz())?
+            .select([
+                col("time"),
+                col("side").cast(DataType::String).str().to_lowercase(),
+                col("price").cast(DataType::Float64),
+                col("size").cast(DataType::Float64),
+            ])
+            .filter(col("side").is_in(lit(Series::new("".into(), sides)), false))
+            .sort(
+                ["time"],
+                SortMultipleOptions::default().with_maintain_order(true),
+            )
+            .collect()?;
+        if df.should_rechunk() {
+            df.rechunk_mut();
+        }
+        Self::validate_tick(&df)?;
+        self.write_tick(code, kind, &mut df)?;
+        Ok(df.height())
+    }
+
+    pub fn scan_tick(
+        &self,
+        code: Codes,
+        kind: &str,
+        begin: Option<Time>,
+        end: Option<Time>,
+    ) -> Result<LazyFrame> {
+        let path = self.find_tick(code, kind).ok_or(anyhow!(
+            "逐笔数据文件不存在: {}",
+            self.tick_path(code, kind).display()
+        ))?;
+        self.scan_range(&path, begin, end)
+    }
+
+    #[inline]
+    pub fn import_book(&self, code: Codes, path: &Path) -> Result<usize> {
+        self.import_tick(code, "book", ["bid", "ask"], path)
+    }
+
+    #[inline]
+    pub fn import_trades(&self, code: Codes, path: &Path) -> Result<usize> {
+        self.import_tick(code, "trades", ["buy", "sell"], path)
+    }
+
+    fn read_tick(&self, code: Codes, kind: &str, begin: Time, end: Time) -> Result<DataFrame> {
+        Ok(self
+            .scan_tick(code, kind, Some(begin), Some(end))?
+            .sort(
+                ["time"],
+                SortMultipleOptions::default().with_maintain_order(true),
+            )
+            .collect()?)
+    }
+
+    /// 按时间加载盘口快照, 买盘价格从高到低, 卖盘价格从低到高
+    pub fn load_books(&self, code: Codes, begin: Time, end: Time) -> Result<Vec<OrderBook>> {
+        let df = self.read_tick(code, "book", begin, end)?;
+        let times = df.column("time")?.cast(&DataType::Int64)?;
+        let times = times.i64()?;
+        let sides = df.column("side")?.str()?;
+        let prices = df.column("price")?.f64()?;
+        let sizes = df.column("size")?.f64()?;
+
+        let mut books: Vec<OrderBook> = vec![];
+        for (((time, side), price), size) in times
+            .into_iter()
+            .zip(sides.into_iter())
+            .zip(prices.into_iter())
+            .zip(sizes.into_iter())
+        {
+            let (Some(time), Some(side), Some(price), Some(size)) = (time, side, price, size)
+            else {
+                continue;
+            };
+            let time = crate::helpers::time::nanos_to_time(time, &self.tz());
+            if books.last().is_none_or(|book| *book.time() != time) {
+                books.push(OrderBook::new(code, time));
+            }
+            let book = books.last().unwrap();
+            match side {
+                "bid" => book.bids_mut().push((price, size)),
+                "ask" => book.asks_mut().push((price, size)),
+                _ => {}
+            }
+        }
+        for book in &books {
+            book.bids_mut()
+                .sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            book.asks_mut()
+                .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        Ok(books)
+    }
+
+    pub fn load_trades(&self, code: Codes, begin: Time, end: Time) -> Result<Vec<Trade>> {
+        let df = self.read_tick(code, "trades", begin, end)?;
+        let times = df.column("time")?.cast(&DataType::Int64)?;
+        let times = times.i64()?;
+        let sides = df.column("side")?.str()?;
+        let prices = df.column("price")?.f64()?;
+        let sizes = df.column("size")?.f64()?;
+
+        let mut trades = Vec::with_capacity(df.height());
+        for (((time, side), price), size) in times
+            .into_iter()
+            .zip(sides.into_iter())
+            .zip(prices.into_iter())
+            .zip(sizes.into_iter())
+        {
+            let (Some(time), Some(side), Some(price), Some(size)) = (time, side, price, size)
+            else {
+                continue;
+            };
+            let side = match side {
+                "buy" => Side::Buy,
+                "sell" => Side::Sell,
+                _ => continue,
+            };
+            trades.push(Trade::from(TradeData {
+                code,
+                time: crate::helpers::time::nanos_to_time(time, &self.tz()),
+                side,
+                price,
+                size,
+            }));
+        }
+
+        Ok(trades)
+    }
+
+    fn load_tick_frame(
+        &self,
+        code: Codes,
+        kind: &str,
+        begin: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<PyDataFrame> {
+        let begin = begin
+            .map(|begin| crate::helpers::time::str_to_time(begin, &self.tz()))
+            .transpose()?;
+        let end = end
+            .map(|end| crate::helpers::time::str_to_time(end, &self.tz()))
+            .transpose()?;
+        Ok(PyDataFrame(
+            self.scan_tick(code, kind, begin, end)?.collect()?,
+        ))
+    }
+
+    pub fn load(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Option<Time>,
+        end: Option<Time>,
+    ) -> Result<DataFrame> {
+        let native = self.exists(code, interval);
+        let mut df = self.scan(
+            code,
+            if native { interval } else { Interval::Min },
+            begin,
+            end,
+        )?;
+
+        if !native && interval != Interval::Min {
+            let every = Duration::parse(interval.abbr());
+            df = df
+                .group_by_dynamic(
+                    col("time"),
+                    Vec::<Expr>::new(),
+                    DynamicGroupOptions {
+                        every,
+                        period: every,
+                        offset: Duration::parse("0ns"),
+                        ..Default::default()
+                    },
+                )
+                .agg([
+                    col("open").first(),
+                    col("high").max(),
+                    col("low").min(),
+                    col("close").last(),
+                    col("volume").sum(),
+                    col("finished").all(true),
+                ]);
+        }
+
+        let mut df = df.collect()?;
+        if df.should_rechunk() {
+            df.rechunk_mut();
+        }
+
+        Ok(df)
+    }
+}
+
+#[pymethods]
+impl DataStore {
+    #[new]
+    #[pyo3(signature = (dir=None, format=DataFormat::Ipc, tz=None))]
+    fn _new(dir: Option<&str>, format: DataFormat, tz: Option<&str>) -> Result<Self> {
+        let tz = match tz {
+            Some(tz) => crate::helpers::time::parse_tz(tz)?,
+            None => DEFAULT_TZ,
+        };
+        Self::new(dir, format, tz)
+    }
+
+    #[getter(dir)]
+    fn py_dir(&self) -> String {
+        self.dir().display().to_string()
+    }
+
+    #[getter(format)]
+    fn py_format(&self) -> DataFormat {
+        *self.format()
+    }
+
+    #[getter(tz)]
+    fn py_tz(&self) -> Tz {
+        *self.tz()
+    }
+
+    #[pyo3(name = "import_file", signature = (code, path, interval=Interval::Min))]
+    fn _import_file(&self, code: Codes, path: &str, interval: Interval) -> Result<usize> {
+        Ok(self.import(code, interval, Path::new(path))?.rows)
+    }
+
+    #[pyo3(name = "import_book", signature = (code, path))]
+    fn _import_book(&self, code: Codes, path: &str) -> Result<usize> {
+        self.import_book(code, Path::new(path))
+    }
+
+    #[pyo3(name = "import_trades", signature = (code, path))]
+    fn _import_trades(&self, code: Codes, path: &str) -> Result<usize> {
+        self.import_trades(code, Path::new(path))
+    }
+
+    #[pyo3(name = "load_book", signature = (code, begin=None, end=None))]
+    fn _load_book(
+        &self,
+        code: Codes,
+        begin: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<PyDataFrame> {
+        self.load_tick_frame(code, "book", begin, end)
+    }
+
+    #[pyo3(name = "load_trades", signature = (code, begin=None, end=None))]
+    fn _load_trades(
+        &self,
+        code: Codes,
+        begin: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<PyDataFrame> {
+        self.load_tick_frame(code, "trades", begin, end)
+    }
+
+    #[pyo3(name = "list", signature = (market=None))]
+    fn _list(&self, market: Option<Market>) -> Result<PyDataFrame> {
+        let mut codes = vec![];
+        let mut markets = vec![];
+        let mut intervals = vec![];
+        let mut rows = vec![];
+        let mut begins = vec![];
+        let mut ends = vec![];
+        let mut gaps = vec![];
+        let mut missing = vec![];
+
+        for item in Market::iter().filter(|item| market.is_none_or(|market| market == *item)) {
+            for (code, interval) in self.list(item)? {
+                let stats = self.stats(code, interval)?;
+                codes.push(code.to_string());
+                markets.push(item.to_string());
+                intervals.push(interval.to_string());
+                rows.push(stats.rows as u64);
+                begins.push(stats.begin);
+                ends.push(stats.end);
+                gaps.push(stats.gaps as u64);
+                missing.push(stats.missing as u64);
+            }
+        }
+
+        let time_type = self.time_type();
+        let df = DataFrame::new(vec![
+            Column::new("code".into(), codes),
+            Column::new("market".into(), markets),
+            Column::new("interval".into(), intervals),
+            Column::new("rows".into(), rows),
+            Column::new("begin".into(), begins).cast(&time_type)?,
+            Column::new("end".into(), ends).cast(&time_type)?,
+            Column::new("gaps".into(), gaps),
+            Column::new("missing".into(), missing),
+        ])?;
+
+        Ok(PyDataFrame(df))
+    }
+
+    #[pyo3(name = "load", signature = (code, begin=None, end=None, interval=Interval::Min))]
+    fn _load(
+        &self,
+        code: Codes,
+        begin: Option<&str>,
+        end: Option<&str>,
+        interval: Interval,
+    ) -> Result<PyDataFrame> {
+        let begin = begin
+            .map(|begin| crate::helpers::time::str_to_time(begin, &self.tz()))
+            .transpose()?;
+        let end = end
+            .map(|end| crate::helpers::time::str_to_time(end, &self.tz()))
+            .transpose()?;
+        Ok(PyDataFrame(self.load(code, interval, begin, end)?))
+    }
+
+    #[pyo3(name = "check", signature = (code, interval=Interval::Min))]
+    fn _check(&self, code: Codes, interval: Interval) -> Result<PyDataFrame> {
+        let items = self.check(code, interval)?;
+        let df = DataFrame::new(vec![
+            Column::new(
+                "check".into(),
+                items.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "count".into(),
+                items
+                    .iter()
+                    .map(|(_, count)| *count as u64)
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "passed".into(),
+                items
+                    .iter()
+                    .map(|(_, count)| *count == 0)
+                    .collect::<Vec<_>>(),
+            ),
+        ])?;
+        Ok(PyDataFrame(df))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_from_times() {
+        let stats = DataStats::from_times(&[0, 60, 60, 240, 180, 300, 600], Some(60));
+        assert_eq!(stats.rows, 7);
+        assert_eq!((stats.begin, stats.end), (Some(0), Some(600)));
+        assert_eq!((stats.gaps, stats.missing), (2, 5));
+        assert_eq!((stats.duplicates, stats.unordered), (1, 1));
+
+        let stats = DataStats::from_times(&[0, 120], None);
+        assert_eq!((stats.gaps, stats.missing), (0, 0));
+
+        let stats = DataStats::from_times(&[], Some(60));
+        assert_eq!((stats.rows, stats.begin), (0, None));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{backtest::Backtest, context::Context};
+use anyhow::{Result, ensure};
+use pyo3::{Bound, PyAny, pymethods, types::PyAnyMethods};
+
+#[pymethods]
+impl Backtest {
+    /// 上下文, 单步调试时在两步之间查看资金, 持仓和订单
+    #[getter(context)]
+    fn py_context(&self) -> Context {
+        self.context().clone()
+    }
+
+    /// 是否已回放到结束时间
+    #[getter]
+    fn finished(&self) -> bool {
+        let cursor = *self.cursor();
+        cursor.is_none_or(|cursor| cursor > *self.end())
+    }
+
+    /// 单步调试: 回放指定数量的有事件的分钟, 返回是否还有未回放的分钟
+    #[pyo3(name = "step", signature = (bars=1))]
+    fn py_step(&self, bars: usize) -> Result<bool> {
+        self.ensure_prepared()?;
+        for _ in 0..bars {
+            if self.step()?.is_none() {
+                break;
+            }
+        }
+        crate::helpers::log::flush()?;
+        Ok(!self.finished())
+    }
+
+    /// 单步调试: 回放到满足任一条件为止, 返回是否还有未回放的分钟
+    /// - 到达指定时间
+    /// - 有订单成交
+    /// - 回调返回真值, 每回放一个有事件的分钟以回测引擎为参数调用一次
+    #[pyo3(signature = (time=None, order=false, predicate=None))]
+    fn step_until(
+        &self,
+        time: Option<&str>,
+        order: bool,
+        predicate: Option<&Bound<PyAny>>,
+    ) -> Result<bool> {
+        self.ensure_prepared()?;
+        ensure!(
+            time.is_some() || order || predicate.is_some(),
+            "至少需要一个停止条件"
+        );
+        let time = time
+            .map(|time| crate::helpers::time::str_to_time(time, &self.context().tz()))
+            .transpose()?;
+
+        while let Some(fills) = self.step()? {
+            if time.is_some_and(|time| *self.context().time() >= time) || (order && fills > 0) {
+                break;
+            }
+            if let Some(predicate) = predicate
+                && predicate.call1((self.clone(),))?.is_truthy()?
+            {
+                break;
+            }
+        }
+        crate::helpers::log::flush()?;
+        Ok(!self.finished())
+    }
+}
+
+impl Backtest {
+    fn ensure_prepared(&self) -> Result<()> {
+        ensure!(*self.debug(), "单步调试需要以debug=True创建回测");
+        ensure!(self.cursor().is_some(), "请先调用launche加载数据");
+        Ok(())
+    }
+}
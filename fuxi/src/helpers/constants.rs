@@ -1,4 +1,6 @@
-pub const OFFSET: chrono_tz::Tz = chrono_tz::Asia::Shanghai;
+pub const DEFAULT_TZ: chrono_tz::Tz = chrono_tz::Asia::Shanghai;
+pub const DATA_DIR_ENV: &str = "FUXI_DATA_DIR";
+pub const RESULTS_DIR_ENV: &str = "FUXI_RESULTS_DIR";
 pub const FMT_MS: &str = "%Y-%m-%d %H:%M:%S%.3f";
 pub const FMT_MS_CPT: &str = "%Y%m%d%H%M%S%3f";
 pub const FMT_S: &str = "%Y-%m-%d %H:%M:%S";
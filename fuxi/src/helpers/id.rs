@@ -1,4 +1,15 @@
+use rand::Rng;
+
 #[inline]
 pub fn new() -> String {
     uuid::Uuid::now_v7().simple().to_string()
 }
+
+/// 由随机数生成器生成id, 相同种子得到相同的id序列
+#[inline]
+pub fn from_rng(rng: &mut impl Rng) -> String {
+    uuid::Builder::from_random_bytes(rng.random())
+        .into_uuid()
+        .simple()
+        .to_string()
+}
@@ -3,7 +3,13 @@ use anyhow::Result;
 //     file_split::{DateType, KeepType, Rolling, RollingType},
 //     packer::LogPacker,
 // };
-use std::{fmt::Arguments, sync::Once};
+use std::{
+    fmt::Arguments,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Once,
+};
 
 static INIT: Once = Once::new();
 
@@ -43,3 +49,47 @@ pub fn flush() -> Result<()> {
 pub fn print(msg: Arguments) {
     let _ = fast_log::print(msg.to_string());
 }
+
+/// 逐行写入的日志文件, 每行一个JSON字符串, 日志不在内存中累积
+pub struct LogFile {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+}
+
+impl LogFile {
+    /// 在[`dir`]下创建临时日志文件, 释放时删除
+    pub fn create(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(".{}.log", crate::helpers::id::new()));
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(Self {
+            path,
+            writer: Some(writer),
+        })
+    }
+
+    pub fn write(&mut self, line: &str) -> Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            serde_json::to_writer(&mut *writer, line)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// 复制已写入的日志, 之后的日志继续写入临时文件
+    pub fn copy_to(&mut self, path: &Path) -> Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        std::fs::copy(&self.path, path)?;
+        Ok(())
+    }
+}
+
+impl Drop for LogFile {
+    fn drop(&mut self) {
+        // 先关闭文件再删除
+        self.writer.take();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -1,24 +1,145 @@
 use crate::{
-    helpers::constants::{FMT_MS, FMT_MS_CPT, OFFSET},
+    helpers::constants::{FMT_MS, FMT_MS_CPT},
     types::alias::Time,
 };
 use anyhow::{Result, anyhow, bail};
-use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+pub fn parse_tz(value: &str) -> Result<Tz> {
+    value
+        .parse::<Tz>()
+        .map_err(|err| anyhow!("无效时区: {value} {err}"))
+}
 
 #[inline]
-pub fn millis_to_time(value: i64) -> Result<Time> {
-    OFFSET
-        .timestamp_millis_opt(value)
+pub fn millis_to_time(value: i64, tz: &Tz) -> Result<Time> {
+    tz.timestamp_millis_opt(value)
         .single()
         .ok_or(anyhow!("无效的时间戳: {value}"))
 }
 
 #[inline]
-pub fn nanos_to_time(value: i64) -> Time {
-    OFFSET.timestamp_nanos(value)
+pub fn nanos_to_time(value: i64, tz: &Tz) -> Time {
+    tz.timestamp_nanos(value)
+}
+
+/// 时间序列化为(纳秒时间戳, 时区名), 用于`#[serde(with = "...")]`
+pub mod serde_time {
+    use crate::types::alias::Time;
+    use chrono::TimeZone;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(time: &Time, serializer: S) -> Result<S::Ok, S::Error> {
+        (
+            time.timestamp_nanos_opt().unwrap_or_default(),
+            time.timezone().name(),
+        )
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Time, D::Error> {
+        let (nanos, tz) = <(i64, String)>::deserialize(deserializer)?;
+        let tz = super::parse_tz(&tz).map_err(D::Error::custom)?;
+        Ok(tz.timestamp_nanos(nanos))
+    }
+}
+
+/// 解析时长: 数字+单位(s/m/h/d/w), 如5m, 4h, 90d
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or(anyhow!("缺少时间单位: {value}"))?;
+    let amount = value[..split].parse::<i64>()?;
+    let period = match &value[split..] {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        unit => bail!("无效时间单位: {unit}"),
+    };
+    Ok(period)
+}
+
+pub fn str_to_time(value: &str, tz: &Tz) -> Result<Time> {
+    let value = value.trim();
+    let (base, delta) = split_relative(value);
+    let time = parse_absolute(base, tz).map_err(|err| anyhow!("无效时间字符串: {value}, {err}"))?;
+    Ok(time + delta)
+}
+
+// 拆分相对时间后缀: now-30d, 2024-01-01+90d, now-1d+8h
+fn split_relative(value: &str) -> (&str, Duration) {
+    let mut base = value;
+    let mut delta = Duration::zero();
+    loop {
+        let bytes = base.as_bytes();
+        let unit_len = bytes
+            .iter()
+            .rev()
+            .take_while(|b| b.is_ascii_alphabetic())
+            .count();
+        if unit_len == 0 || unit_len == bytes.len() {
+            break;
+        }
+        let rest = &bytes[..bytes.len() - unit_len];
+        let digits = rest.iter().rev().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 || digits == rest.len() {
+            break;
+        }
+        let sign = rest[rest.len() - digits - 1];
+        if sign != b'+' && sign != b'-' {
+            break;
+        }
+
+        let Ok(step) = parse_duration(&base[rest.len() - digits..]) else {
+            break;
+        };
+        delta = if sign == b'+' {
+            delta + step
+        } else {
+            delta - step
+        };
+        base = &base[..rest.len() - digits - 1];
+    }
+    (base, delta)
+}
+
+fn parse_absolute(value: &str, tz: &Tz) -> Result<Time> {
+    if value.eq_ignore_ascii_case("now") {
+        return Ok(now(tz));
+    }
+
+    if let Some(secs) = value.strip_prefix('@') {
+        return seconds_to_time(secs.parse::<i64>()?, tz);
+    }
+
+    if value.bytes().all(|b| b.is_ascii_digit()) {
+        if value.len() == 13 {
+            return millis_to_time(value.parse::<i64>()?, tz);
+        }
+        return match parse_local(value, tz) {
+            Ok(time) => Ok(time),
+            Err(_) if value.len() == 10 => seconds_to_time(value.parse::<i64>()?, tz),
+            Err(err) => Err(err),
+        };
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(tz));
+    }
+    for fmt in ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%d %H:%M%:z"] {
+        if let Ok(time) = DateTime::parse_from_str(value, fmt) {
+            return Ok(time.with_timezone(tz));
+        }
+    }
+
+    parse_local(&value.replacen('T', " ", 1), tz)
 }
 
-pub fn str_to_time(value: &str) -> Result<Time> {
+fn parse_local(value: &str, tz: &Tz) -> Result<Time> {
     let len = value.len();
     let naive = if value.contains("-") {
         let ds = match len {
@@ -28,7 +149,7 @@ pub fn str_to_time(value: &str) -> Result<Time> {
             16 => format!("{value}:00.000"),
             19 => format!("{value}.000"),
             23 => value.to_owned(),
-            _ => bail!("无效时间字符串: {value}"),
+            _ => bail!("不支持的长度: {len}"),
         };
         NaiveDateTime::parse_from_str(&ds, FMT_MS)?
     } else {
@@ -40,23 +161,88 @@ pub fn str_to_time(value: &str) -> Result<Time> {
             12 => format!("{value}00000"),
             14 => format!("{value}000"),
             17 => value.to_owned(),
-            _ => bail!("无效时间字符串: {value}"),
+            _ => bail!("不支持的长度: {len}"),
         };
         NaiveDateTime::parse_from_str(&ds, FMT_MS_CPT)?
     };
 
-    OFFSET
-        .from_local_datetime(&naive)
+    tz.from_local_datetime(&naive)
         .single()
-        .ok_or(anyhow!("无效时间字符串: {value}"))
+        .ok_or(anyhow!("本地时间不存在或有歧义: {naive}"))
+}
+
+#[inline]
+fn seconds_to_time(value: i64, tz: &Tz) -> Result<Time> {
+    tz.timestamp_opt(value, 0)
+        .single()
+        .ok_or(anyhow!("无效的时间戳: {value}"))
 }
 
 pub fn time_to_str(value: Time, fmt: &str) -> String {
     value.format(fmt).to_string()
 }
 
-#[allow(dead_code)]
 #[inline]
-pub fn now() -> Time {
-    Utc::now().with_timezone(&OFFSET)
+pub fn now(tz: &Tz) -> Time {
+    Utc::now().with_timezone(tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Asia::Shanghai, UTC};
+
+    #[test]
+    fn split_relative_offsets() {
+        assert_eq!(split_relative("now-30d"), ("now", -Duration::days(30)));
+        assert_eq!(
+            split_relative("2024-01-01+90d"),
+            ("2024-01-01", Duration::days(90))
+        );
+        assert_eq!(
+            split_relative("now-1d+8h"),
+            ("now", -Duration::days(1) + Duration::hours(8))
+        );
+        // 日期中的减号和没有单位的数字不是偏移
+        assert_eq!(
+            split_relative("2024-01-01"),
+            ("2024-01-01", Duration::zero())
+        );
+        assert_eq!(split_relative("20240101"), ("20240101", Duration::zero()));
+        // 无效单位保留原样
+        assert_eq!(split_relative("now-3x"), ("now-3x", Duration::zero()));
+    }
+
+    #[test]
+    fn str_to_time_formats() {
+        let expected = Shanghai.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        for value in [
+            "2024-01-02 03:04:05",
+            "2024-01-02T03:04:05",
+            "20240102030405",
+            "2024-01-01T19:04:05Z",
+            "2024-01-02T03:04:05+08:00",
+            "@1704135845",
+            "1704135845000",
+            "2024-01-01 03:04:05+1d",
+        ] {
+            assert_eq!(str_to_time(value, &Shanghai).unwrap(), expected, "{value}");
+        }
+
+        assert_eq!(
+            str_to_time("2024-01", &UTC).unwrap(),
+            UTC.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert!(str_to_time("2024-01-02 03", &UTC).is_ok());
+        assert!(str_to_time("yesterday", &UTC).is_err());
+        assert!(str_to_time("2024-13-01", &UTC).is_err());
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("5m").unwrap(), Duration::minutes(5));
+        assert_eq!(parse_duration(" 1w ").unwrap(), Duration::weeks(1));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5y").is_err());
+    }
 }
@@ -1,93 +1,162 @@
 use crate::{
     context::Context,
-    types::base::{Codes, LogLevel, Market},
+    data_store::DataStore,
+    providers::{DataProvider, retryable},
+    types::{
+        alias::Time,
+        base::{Codes, Interval, LogLevel},
+    },
 };
-use anyhow::{Result, ensure};
+use anyhow::Result;
+use chrono_tz::Tz;
 use polars::prelude::*;
-use tokio::{fs::OpenOptions, io::AsyncWriteExt, time::Instant};
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::Semaphore, time::Instant};
 
-const DOWNLOAD_PREFIX: &str = "https://raw.githubusercontent.com/FrequentHippos/freqtrade_hyperliquid_download-data/refs/heads/main/user_data/data/hyperliquid/";
+#[derive(Clone)]
+pub struct DownloadOptions {
+    pub force: bool,
+    pub concurrency: usize,
+    pub retries: usize,
+}
+
+#[derive(Default)]
+pub struct DownloadReport {
+    pub synced: Vec<Codes>,
+    pub skipped: Vec<Codes>,
+    pub failed: Vec<(Codes, String)>,
+    /// 本地数据通过校验的交易对, 同步失败时可以使用本地数据
+    pub verified: Vec<Codes>,
+}
+
+impl DownloadReport {
+    pub fn failed_summary(&self) -> String {
+        self.failed
+            .iter()
+            .map(|(code, err)| format!("{code}: {err}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
 
 #[tokio::main(flavor = "current_thread")]
-pub async fn download(context: Context, codes: &[Codes], force: bool) -> Result<()> {
-    let dir = std::env::current_dir()?.join("data");
-    let spot_dir = dir.join("spot");
-    let swap_dir = dir.join("swap");
-    std::fs::create_dir_all(&spot_dir)?;
-    std::fs::create_dir_all(&swap_dir)?;
+pub async fn download(
+    context: Context,
+    store: DataStore,
+    provider: Arc<dyn DataProvider>,
+    codes: &[Codes],
+    begin: Time,
+    end: Time,
+    options: DownloadOptions,
+) -> Result<DownloadReport> {
+    store.prepare()?;
+
+    let mut report = DownloadReport::default();
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
 
     let mut handles = vec![];
     for code in codes {
         let context = context.clone();
+        let provider = provider.clone();
+        let semaphore = semaphore.clone();
+        let retries = options.retries;
         let code = *code;
 
-        let save_path = match code.market() {
-            Market::Spot => spot_dir.join(format!(
-                "{}.feather",
-                code.code().replace("/", "_").replace(":", "_")
-            )),
-            Market::Swap => swap_dir.join(format!(
-                "{}.feather",
-                code.code().replace("/", "_").replace(":", "_")
-            )),
+        // 本地数据的时间范围, 强制同步或校验失败时重新下载全部
+        let local = if options.force {
+            None
+        } else {
+            match store.verify(code, Interval::Min) {
+                Ok(manifest) => {
+                    report.verified.push(code);
+                    let time =
+                        |nanos| crate::helpers::time::nanos_to_time(nanos, &begin.timezone());
+                    manifest
+                        .begin
+                        .zip(manifest.end)
+                        .map(|(first, last)| (time(first), time(last)))
+                }
+                Err(err) => {
+                    if store.exists(code, Interval::Min) {
+                        context.show_log(
+                            LogLevel::Warn,
+                            format_args!("本地数据无效, 重新下载 交易对: {code}, 原因: {err}"),
+                        );
+                    }
+                    None
+                }
+            }
+        };
+        // 只下载本地缺少的部分: 起始时间提前时向前回补, 结束时间推后时向后追加
+        let ranges = match local {
+            Some((first, last)) => {
+                let mut ranges = vec![];
+                if first > begin {
+                    ranges.push((begin, first - chrono::Duration::minutes(1)));
+                }
+                if last < end {
+                    ranges.push((last + chrono::Duration::minutes(1), end));
+                }
+                ranges
+            }
+            None => vec![(begin, end)],
         };
-        if !force && save_path.exists() {
+        if ranges.is_empty() {
+            report.skipped.push(code);
             continue;
         }
-
-        let download_path = format!(
-            "{DOWNLOAD_PREFIX}{}",
-            match code.market() {
-                Market::Spot => format!(
-                    "{}-1m.feather",
-                    code.code().replace("/", "_").replace(":", "_")
-                ),
-                Market::Swap => format!(
-                    "futures/{}-1m-futures.feather",
-                    code.code().replace("/", "_").replace(":", "_")
-                ),
-            }
-        );
+        let store = store.clone();
 
         let handle = tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
             let start_time = Instant::now();
 
-            context.show_log(LogLevel::Debug, format_args!("下载k线 {code}..."));
-
-            let response = reqwest::get(download_path).await?;
-
-            ensure!(
-                response.status().is_success(),
-                "下载失败 交易对: {code}, 状态: {}",
-                response.status()
-            );
+            let mut frames = vec![];
+            for (fetch_begin, fetch_end) in ranges {
+                context.show_log(
+                    LogLevel::Debug,
+                    format_args!(
+                        "下载k线 {code}, 数据源: {}, 区间: {} ~ {}...",
+                        provider.name(),
+                        fetch_begin.format(crate::helpers::constants::FMT_S),
+                        fetch_end.format(crate::helpers::constants::FMT_S),
+                    ),
+                );
 
-            let mut file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(save_path.as_path())
-                .await?;
-            let bytes = response.bytes().await?;
-            file.write_all(&bytes).await?;
-            file.flush().await?;
-            drop(file);
+                let mut attempt = 0;
+                let df = loop {
+                    match provider
+                        .fetch(code, Interval::Min, fetch_begin, fetch_end)
+                        .await
+                    {
+                        Ok(df) => break df,
+                        Err(err) if attempt < retries && retryable(&err) => {
+                            let delay = Duration::from_secs(1 << attempt.min(6));
+                            attempt += 1;
+                            context.show_log(
+                                LogLevel::Warn,
+                                format_args!(
+                                    "下载k线失败 交易对: {code}, 重试: {attempt}/{retries}, 等待: {}, 原因: {err}",
+                                    humantime::format_duration(delay)
+                                ),
+                            );
+                            tokio::time::sleep(delay).await;
+                        }
+                        Err(err) => return Err(err),
+                    }
+                };
+                frames.push(df.lazy());
+            }
 
             let _context = context.clone();
             tokio::task::spawn_blocking(move || {
-                let mut df = LazyFrame::scan_ipc(
-                    PlPathRef::from_local_path(save_path.as_path()).into_owned(),
-                    Default::default(),
-                )?;
-                df = df.rename(["date"], ["time"], true);
-                df = df.with_column(
-                    col("time")
-                        .dt()
-                        .convert_time_zone(TimeZone::from_chrono(&chrono_tz::Asia::Shanghai)),
-                );
-                df = df.with_column(lit(true).alias("finished"));
-
-                let mut df = df.collect()?;
+                if local.is_some() {
+                    frames.insert(0, store.scan(code, Interval::Min, None, None)?);
+                }
+                let mut df = concat(frames, Default::default())?
+                    .sort(["time"], Default::default())
+                    .unique_stable_generic(Some(vec!["time"]), UniqueKeepStrategy::Last)
+                    .collect()?;
 
                 if df.should_rechunk() {
                     df.rechunk_mut();
@@ -95,13 +164,7 @@ pub async fn download(context: Context, codes: &[Codes], force: bool) -> Result<
 
                 _context.show_log(LogLevel::Debug, format_args!("{code} {df}"));
 
-                let mut file = std::fs::OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .truncate(true)
-                    .open(save_path.as_path())?;
-
-                IpcWriter::new(&mut file).finish(&mut df)?;
+                store.write(code, Interval::Min, &mut df)?;
 
                 anyhow::Ok(())
             })
@@ -118,13 +181,161 @@ pub async fn download(context: Context, codes: &[Codes], force: bool) -> Result<
 
             anyhow::Ok(())
         });
-        handles.push(handle);
+        handles.push((code, handle));
         tokio::task::yield_now().await;
     }
 
-    for handle in handles {
-        handle.await??;
+    for (code, handle) in handles {
+        match handle.await {
+            Ok(Ok(())) => report.synced.push(code),
+            Ok(Err(err)) => report.failed.push((code, err.to_string())),
+            Err(err) => report.failed.push((code, err.to_string())),
+        }
+    }
+
+    context.show_log(
+        if report.failed.is_empty() {
+            LogLevel::Debug
+        } else {
+            LogLevel::Warn
+        },
+        format_args!(
+            "下载k线汇总 同步: {:?}, 跳过: {:?}, 失败: [{}]",
+            report.synced,
+            report.skipped,
+            report.failed_summary(),
+        ),
+    );
+
+    Ok(report)
+}
+
+/// 缺失数据区间: (开始时间, 结束时间, 缺失条数)
+pub type Gap = (Time, Time, usize);
+
+pub fn find_gaps(df: &DataFrame, tz: &Tz) -> Result<Vec<Gap>> {
+    let times = df.column("time")?.cast(&DataType::Int64)?;
+    let times = times.i64()?;
+    let missing = df.column("missing")?.bool()?;
+
+    let mut gaps: Vec<Gap> = vec![];
+    let mut current: Option<Gap> = None;
+    for (time, is_missing) in times.into_iter().zip(missing.into_iter()) {
+        let Some(time) = time else {
+            continue;
+        };
+        let time = crate::helpers::time::nanos_to_time(time, tz);
+        if is_missing.unwrap_or(false) {
+            current = match current {
+                Some((begin, _, count)) => Some((begin, time, count + 1)),
+                None => Some((time, time, 1)),
+            };
+        } else if let Some(gap) = current.take() {
+            gaps.push(gap);
+        }
+    }
+    if let Some(gap) = current {
+        gaps.push(gap);
+    }
+
+    Ok(gaps)
+}
+
+pub fn report_gaps(context: &Context, code: Codes, total: usize, gaps: &[Gap]) {
+    const MAX_SHOW: usize = 10;
+
+    if gaps.is_empty() {
+        context.show_log(
+            LogLevel::Debug,
+            format_args!("数据质量 交易对: {code}, 总数: {total}, 无缺失"),
+        );
+        return;
+    }
+
+    let missing = gaps.iter().map(|(_, _, count)| count).sum::<usize>();
+    let mut ranges = gaps
+        .iter()
+        .take(MAX_SHOW)
+        .map(|(begin, end, count)| {
+            format!(
+                "{} ~ {} ({count})",
+                begin.format(crate::helpers::constants::FMT_S),
+                end.format(crate::helpers::constants::FMT_S),
+            )
+        })
+        .collect::<Vec<_>>();
+    if gaps.len() > MAX_SHOW {
+        ranges.push(format!("...共{}段", gaps.len()));
+    }
+
+    context.show_log(
+        LogLevel::Warn,
+        format_args!(
+            "数据质量 交易对: {code}, 总数: {total}, 缺失: {missing}, 区间: [{}]",
+            ranges.join(", ")
+        ),
+    );
+}
+
+pub fn check_range(
+    context: &Context,
+    store: &DataStore,
+    code: Codes,
+    begin: Time,
+    end: Time,
+) -> Result<()> {
+    let Some(meta) = store.meta(code, Interval::Min)? else {
+        return Ok(());
+    };
+
+    let covered = match (meta.begin, meta.end) {
+        (Some(first), Some(last)) => first <= begin && last >= end,
+        _ => false,
+    };
+    if !covered {
+        let fmt = |time: Option<Time>| {
+            time.map(|time| time.format(crate::helpers::constants::FMT_S).to_string())
+                .unwrap_or("-".to_string())
+        };
+        context.show_log(
+            LogLevel::Warn,
+            format_args!(
+                "数据范围不足 交易对: {code}, 请求: {} ~ {}, 可用: {} ~ {}",
+                fmt(Some(begin)),
+                fmt(Some(end)),
+                fmt(meta.begin),
+                fmt(meta.end),
+            ),
+        );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::UTC;
+
+    const MINUTE: i64 = 60_000_000_000;
+
+    #[test]
+    fn find_gaps_groups_missing_rows() {
+        let df = df!(
+            "time" => (0..8).map(|index| index * MINUTE).collect::<Vec<_>>(),
+            "missing" => [false, true, true, false, true, false, true, true],
+        )
+        .unwrap();
+        let gaps = find_gaps(&df, &UTC).unwrap();
+
+        let time = |index| crate::helpers::time::nanos_to_time(index * MINUTE, &UTC);
+        assert_eq!(
+            gaps,
+            vec![
+                (time(1), time(2), 2),
+                (time(4), time(4), 1),
+                (time(6), time(7), 2),
+            ]
+        );
+    }
+}
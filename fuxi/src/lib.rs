@@ -1,20 +1,34 @@
+mod attribution;
 mod backtest;
+mod checkpoint;
 mod context;
+mod data_store;
+mod debugger;
 mod helpers;
 mod history_data;
+mod matching;
+mod metrics;
+mod monte_carlo;
+mod optimize;
+mod providers;
+mod report;
+mod results;
 mod runtime;
+mod scheduler;
 mod strategy;
 mod types;
 
 use crate::{
     backtest::Backtest,
     context::Context,
+    data_store::DataStore,
+    results::{ResultStore, Run},
     types::{
         base::{
-            Codes, Coins, Direction, Interval, LogLevel, Market, Method, Mode, OrderStatus, Pnl,
-            Side, Timer, Volume,
+            Codes, Coins, DataFormat, DataSource, Direction, GapPolicy, Interval, LogLevel, Market,
+            Method, Mode, OrderStatus, Pnl, Resample, Side, Timer, Volume,
         },
-        market::{Candle, FundingRate, Symbol},
+        market::{Candle, FundingRate, OrderBook, Symbol, Trade},
         order::Order,
         position::{Position, SidePosition},
     },
@@ -35,6 +49,10 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<OrderStatus>()?;
     m.add_class::<Interval>()?;
     m.add_class::<Timer>()?;
+    m.add_class::<GapPolicy>()?;
+    m.add_class::<Resample>()?;
+    m.add_class::<DataSource>()?;
+    m.add_class::<DataFormat>()?;
     m.add_class::<Coins>()?;
     m.add_class::<Codes>()?;
     m.add_class::<Volume>()?;
@@ -44,8 +62,13 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Position>()?;
     m.add_class::<Candle>()?;
     m.add_class::<FundingRate>()?;
+    m.add_class::<OrderBook>()?;
+    m.add_class::<Trade>()?;
     m.add_class::<Symbol>()?;
     m.add_class::<Context>()?;
     m.add_class::<Backtest>()?;
+    m.add_class::<DataStore>()?;
+    m.add_class::<ResultStore>()?;
+    m.add_class::<Run>()?;
     Ok(())
 }
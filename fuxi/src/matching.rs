@@ -0,0 +1,349 @@
+use crate::types::{
+    alias::{Price, Size, Time},
+    base::{Method, Side},
+    market::{OrderBook, Trade},
+    order::Order,
+};
+use rust_decimal::{
+    Decimal, RoundingStrategy,
+    prelude::{FromPrimitive, ToPrimitive},
+};
+
+pub struct Fill {
+    pub size: Size,
+    pub price: Price,
+    pub maker: bool,
+}
+
+impl Fill {
+    fn new(order: &Order, size: f64, price: f64, maker: bool) -> Option<Self> {
+        let size = Decimal::from_f64(size)?.round_dp_with_strategy(
+            order.code().decimals().to_u32().unwrap_or_default(),
+            RoundingStrategy::ToZero,
+        );
+        if size <= Decimal::ZERO {
+            return None;
+        }
+        Some(Self {
+            size,
+            price: Decimal::from_f64(price)?,
+            maker,
+        })
+    }
+}
+
+pub trait Timed {
+    fn event_time(&self) -> Time;
+}
+
+impl Timed for OrderBook {
+    #[inline]
+    fn event_time(&self) -> Time {
+        *self.time()
+    }
+}
+
+impl Timed for Trade {
+    #[inline]
+    fn event_time(&self) -> Time {
+        *self.time()
+    }
+}
+
+/// 按时间回放的事件序列(盘口快照, 逐笔成交)
+pub struct Replay<T> {
+    items: Vec<T>,
+    cursor: usize,
+}
+
+impl<T: Timed + Clone> Replay<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self { items, cursor: 0 }
+    }
+
+    /// 下一个未回放事件的时间
+    #[inline]
+    pub fn next_time(&self) -> Option<Time> {
+        self.items.get(self.cursor).map(|item| item.event_time())
+    }
+
+    /// 推进到指定时间, 返回推进前的最后一个事件和新到达的事件
+    pub fn advance(&mut self, time: &Time) -> (Option<T>, Vec<T>) {
+        let start = self.cursor;
+        while self.cursor < self.items.len() && self.items[self.cursor].event_time() <= *time {
+            self.cursor += 1;
+        }
+        (
+            start.checked_sub(1).map(|index| self.items[index].clone()),
+            self.items[start..self.cursor].to_vec(),
+        )
+    }
+}
+
+#[inline]
+fn remaining(order: &Order) -> f64 {
+    (*order.size() - *order.deal_size())
+        .to_f64()
+        .unwrap_or_default()
+}
+
+/// 吃掉对手盘深度, 返回成交量和均价
+fn sweep(levels: &[(f64, f64)], size: f64, limit: Option<f64>, is_buy: bool) -> (f64, f64) {
+    let mut filled = 0.0;
+    let mut value = 0.0;
+    for (price, level) in levels {
+        if filled >= size {
+            break;
+        }
+        if let Some(limit) = limit
+            && (if is_buy {
+                *price > limit
+            } else {
+                *price < limit
+            })
+        {
+            break;
+        }
+        let take = level.min(size - filled);
+        filled += take;
+        value += take * price;
+    }
+    (filled, if filled > 0.0 { value / filled } else { 0.0 })
+}
+
+/// 排在限价单前面的挂单量, 下单时取同价位的挂单量
+pub fn queue_ahead(order: &Order, book: &OrderBook) -> f64 {
+    let price = order.price().to_f64().unwrap_or_default();
+    book.level_size(*order.side() == Side::Buy, price)
+}
+
+/// 盘口撮合
+/// - 市价单和穿价的限价单按对手盘深度成交
+/// - 挂单按排队位置成交: 同价位挂单量的减少视为成交, 先消耗排在前面的量
+pub fn match_book(
+    order: &Order,
+    queue: &mut f64,
+    prev: Option<&OrderBook>,
+    book: &OrderBook,
+) -> Option<Fill> {
+    let is_buy = *order.side() == Side::Buy;
+    let size = remaining(order);
+    let price = order.price().to_f64().unwrap_or_default();
+    let opposite = if is_buy { book.asks() } else { book.bids() };
+
+    if *order.method() == Method::Market {
+        let (filled, avg) = sweep(&opposite, size, None, is_buy);
+        return Fill::new(order, filled, avg, false);
+    }
+
+    let crossed = opposite.first().is_some_and(|(best, _)| {
+        if is_buy {
+            *best <= price
+        } else {
+            *best >= price
+        }
+    });
+    if crossed {
+        let (filled, avg) = sweep(&opposite, size, Some(price), is_buy);
+        return Fill::new(order, filled, avg, false);
+    }
+    drop(opposite);
+
+    let level = book.level_size(is_buy, price);
+    let traded = prev
+        .map(|prev| (prev.level_size(is_buy, price) - level).max(0.0))
+        .unwrap_or_default();
+    let used = queue.min(traded);
+    *queue = (*queue - used).min(level);
+
+    Fill::new(order, (traded - used).min(size), price, true)
+}
+
+/// K线撮合, 没有盘口数据时使用
+/// - 市价单按收盘价成交
+/// - 限价单在价格穿过挂单价时全部成交
+pub fn match_candle(order: &Order, high: f64, low: f64, close: f64) -> Option<Fill> {
+    let size = remaining(order);
+    let price = order.price().to_f64().unwrap_or_default();
+    match (*order.method(), *order.side()) {
+        (Method::Market, _) => Fill::new(order, size, close, false),
+        (Method::Limit, Side::Buy) if low < price => Fill::new(order, size, price, true),
+        (Method::Limit, Side::Sell) if high > price => Fill::new(order, size, price, true),
+        _ => None,
+    }
+}
+
+/// 逐笔成交撮合
+/// - 市价单按成交价成交, 不超过该笔成交量
+/// - 限价单在成交价穿过挂单价时成交, 成交价等于挂单价时先消耗排在前面的量
+pub fn match_trade(order: &Order, queue: &mut f64, trade: &Trade) -> Option<Fill> {
+    let is_buy = *order.side() == Side::Buy;
+    let size = remaining(order);
+    let price = order.price().to_f64().unwrap_or_default();
+    let (trade_price, trade_size) = (*trade.price(), *trade.size());
+
+    if *order.method() == Method::Market {
+        return Fill::new(order, size.min(trade_size), trade_price, false);
+    }
+
+    let through = if is_buy {
+        trade_price < price
+    } else {
+        trade_price > price
+    };
+    if through {
+        return Fill::new(order, size.min(trade_size), price, true);
+    }
+
+    // 只有对手方主动成交才会消耗本方挂单
+    if trade_price != price || (*trade.side() == Side::Buy) == is_buy {
+        return None;
+    }
+    let used = queue.min(trade_size);
+    *queue -= used;
+
+    Fill::new(order, (trade_size - used).min(size), price, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::constants::DEFAULT_TZ,
+        types::{
+            base::{Codes, Direction, OrderStatus},
+            market::TradeData,
+            order::OrderData,
+        },
+    };
+    use chrono::TimeZone;
+    use rust_decimal::dec;
+
+    fn time() -> Time {
+        DEFAULT_TZ.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn order(method: Method, side: Side, size: Size, price: Price) -> Order {
+        Order::from(OrderData {
+            code: Codes::BTC_SWAP,
+            id: "order".to_string(),
+            method,
+            direction: Direction::Long,
+            side,
+            status: OrderStatus::Pending,
+            size,
+            price,
+            deal_size: Decimal::ZERO,
+            deal_price: Decimal::ZERO,
+            deal_fee: Decimal::ZERO,
+            margin: Decimal::ZERO,
+            remark: None,
+            create_time: time(),
+            update_time: time(),
+        })
+    }
+
+    fn book(bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> OrderBook {
+        let book = OrderBook::new(Codes::BTC_SWAP, time());
+        book.set_bids(bids);
+        book.set_asks(asks);
+        book
+    }
+
+    fn trade(side: Side, price: f64, size: f64) -> Trade {
+        Trade::from(TradeData {
+            code: Codes::BTC_SWAP,
+            time: time(),
+            side,
+            price,
+            size,
+        })
+    }
+
+    #[test]
+    fn book_sweeps_opposite_levels() {
+        let book = book(vec![(99.0, 5.0)], vec![(100.0, 1.0), (101.0, 1.0)]);
+
+        let market = order(Method::Market, Side::Buy, dec!(1.5), Decimal::ZERO);
+        let fill = match_book(&market, &mut 0.0, None, &book).unwrap();
+        assert_eq!(fill.size, dec!(1.5));
+        assert!((fill.price.to_f64().unwrap() - 301.0 / 3.0).abs() < 1e-9);
+        assert!(!fill.maker);
+
+        // 穿价限价单不吃超过限价的档位
+        let limit = order(Method::Limit, Side::Buy, dec!(2), dec!(100.5));
+        let fill = match_book(&limit, &mut 0.0, None, &book).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(1), dec!(100)));
+        assert!(!fill.maker);
+    }
+
+    #[test]
+    fn book_consumes_queue_before_filling() {
+        let limit = order(Method::Limit, Side::Buy, dec!(1), dec!(99));
+        let prev = book(vec![(99.0, 5.0)], vec![(100.0, 1.0)]);
+        let mut queue = queue_ahead(&limit, &prev);
+        assert_eq!(queue, 5.0);
+
+        let next = book(vec![(99.0, 3.0)], vec![(100.0, 1.0)]);
+        assert!(match_book(&limit, &mut queue, Some(&prev), &next).is_none());
+        assert_eq!(queue, 3.0);
+
+        let last = book(vec![(99.0, 0.5)], vec![(100.0, 1.0)]);
+        assert!(match_book(&limit, &mut queue, Some(&next), &last).is_none());
+        assert_eq!(queue, 0.5);
+
+        let gone = book(vec![], vec![(100.0, 1.0)]);
+        assert!(match_book(&limit, &mut queue, Some(&last), &gone).is_none());
+        assert_eq!(queue, 0.0);
+
+        let refill = book(vec![(99.0, 2.0)], vec![(100.0, 1.0)]);
+        let drained = book(vec![(99.0, 1.25)], vec![(100.0, 1.0)]);
+        assert!(match_book(&limit, &mut queue, Some(&gone), &refill).is_none());
+        let fill = match_book(&limit, &mut queue, Some(&refill), &drained).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(0.75), dec!(99)));
+        assert!(fill.maker);
+    }
+
+    #[test]
+    fn trade_fills_after_queue() {
+        let limit = order(Method::Limit, Side::Buy, dec!(2), dec!(99));
+        let mut queue = 1.0;
+
+        // 同方向主动成交不消耗买单
+        assert!(match_trade(&limit, &mut queue, &trade(Side::Buy, 99.0, 5.0)).is_none());
+        assert_eq!(queue, 1.0);
+
+        let fill = match_trade(&limit, &mut queue, &trade(Side::Sell, 99.0, 1.5)).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(0.5), dec!(99)));
+        assert_eq!(queue, 0.0);
+
+        let fill = match_trade(&limit, &mut queue, &trade(Side::Sell, 98.0, 5.0)).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(2), dec!(99)));
+
+        let market = order(Method::Market, Side::Sell, dec!(3), Decimal::ZERO);
+        let fill = match_trade(&market, &mut 0.0, &trade(Side::Buy, 101.0, 1.0)).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(1), dec!(101)));
+        assert!(!fill.maker);
+    }
+
+    #[test]
+    fn candle_crosses_limit_price() {
+        let buy = order(Method::Limit, Side::Buy, dec!(1), dec!(99));
+        assert!(match_candle(&buy, 101.0, 99.0, 100.0).is_none());
+        let fill = match_candle(&buy, 101.0, 98.5, 100.0).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(1), dec!(99)));
+
+        let sell = order(Method::Limit, Side::Sell, dec!(1), dec!(101));
+        assert!(match_candle(&sell, 101.0, 99.0, 100.0).is_none());
+        assert!(match_candle(&sell, 101.5, 99.0, 100.0).is_some());
+
+        let market = order(Method::Market, Side::Buy, dec!(0.5), Decimal::ZERO);
+        let fill = match_candle(&market, 101.0, 99.0, 100.0).unwrap();
+        assert_eq!((fill.size, fill.price), (dec!(0.5), dec!(100)));
+        assert!(!fill.maker);
+
+        // 低于最小精度的剩余量不成交
+        let dust = order(Method::Market, Side::Buy, dec!(0.00000001), Decimal::ZERO);
+        assert!(match_candle(&dust, 101.0, 99.0, 100.0).is_none());
+    }
+}
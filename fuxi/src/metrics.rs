@@ -0,0 +1,308 @@
+use crate::types::order::Order;
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use polars::prelude::*;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+pub const DAY_NANOS: i64 = 24 * 60 * 60 * 1_000_000_000;
+
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+/// 权益曲线: (纳秒时间戳, 权益)
+pub type Equity = [(i64, f64)];
+
+#[derive(Default, Clone)]
+pub struct Metrics {
+    pub final_equity: f64,
+    pub total_return: f64,
+    pub annual_return: f64,
+    pub volatility: f64,
+    pub sharpe: f64,
+    pub max_drawdown: f64,
+    pub orders: usize,
+    pub fills: usize,
+    pub fees: f64,
+}
+
+impl Metrics {
+    /// 日收益按时区的自然日划分
+    pub fn compute(equity: &Equity, orders: &[Order], tz: &Tz) -> Self {
+        let mut metrics = Self {
+            orders: orders.len(),
+            fills: orders
+                .iter()
+                .filter(|order| *order.deal_size() > Decimal::ZERO)
+                .count(),
+            fees: orders
+                .iter()
+                .map(|order| order.deal_fee().to_f64().unwrap_or_default())
+                .sum(),
+            ..Default::default()
+        };
+
+        let (Some((begin, initial)), Some((end, last))) = (equity.first(), equity.last()) else {
+            return metrics;
+        };
+        metrics.final_equity = *last;
+        if *initial > 0.0 {
+            metrics.total_return = last / initial - 1.0;
+        }
+
+        let days = (end - begin) as f64 / DAY_NANOS as f64;
+        if days > 0.0 && metrics.total_return > -1.0 {
+            metrics.annual_return = (1.0 + metrics.total_return).powf(DAYS_PER_YEAR / days) - 1.0;
+        }
+
+        let returns = daily_returns(equity, tz);
+        let (mean, std) = mean_std(&returns);
+        metrics.volatility = std * DAYS_PER_YEAR.sqrt();
+        if std > 0.0 {
+            metrics.sharpe = mean / std * DAYS_PER_YEAR.sqrt();
+        }
+        metrics.max_drawdown = max_drawdown(equity);
+
+        metrics
+    }
+
+    /// 按列名取指标, 用于参数优化时选择目标
+    pub fn get(&self, name: &str) -> Option<f64> {
+        Some(match name {
+            "final_equity" => self.final_equity,
+            "total_return" => self.total_return,
+            "annual_return" => self.annual_return,
+            "volatility" => self.volatility,
+            "sharpe" => self.sharpe,
+            "max_drawdown" => self.max_drawdown,
+            "orders" => self.orders as f64,
+            "fills" => self.fills as f64,
+            "fees" => self.fees,
+            _ => return None,
+        })
+    }
+
+    pub fn to_columns(items: &[Self]) -> Vec<Column> {
+        let f64s = |name: &str, get: fn(&Self) -> f64| {
+            Column::new(name.into(), items.iter().map(get).collect::<Vec<_>>())
+        };
+        let u64s = |name: &str, get: fn(&Self) -> usize| {
+            Column::new(
+                name.into(),
+                items
+                    .iter()
+                    .map(|item| get(item) as u64)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        vec![
+            f64s("final_equity", |item| item.final_equity),
+            f64s("total_return", |item| item.total_return),
+            f64s("annual_return", |item| item.annual_return),
+            f64s("volatility", |item| item.volatility),
+            f64s("sharpe", |item| item.sharpe),
+            f64s("max_drawdown", |item| item.max_drawdown),
+            u64s("orders", |item| item.orders),
+            u64s("fills", |item| item.fills),
+            f64s("fees", |item| item.fees),
+        ]
+    }
+}
+
+/// 相对基准的指标, 按日收益计算并年化
+#[derive(Default, Clone)]
+pub struct Relative {
+    pub benchmark_return: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    pub correlation: f64,
+    pub tracking_error: f64,
+    pub information_ratio: f64,
+}
+
+impl Relative {
+    /// 策略和基准的权益曲线需要使用相同的时间, 日收益按时区的自然日划分
+    pub fn compute(equity: &Equity, benchmark: &Equity, tz: &Tz) -> Self {
+        let mut relative = Self::default();
+        if let (Some((_, initial)), Some((_, last))) = (benchmark.first(), benchmark.last())
+            && *initial > 0.0
+        {
+            relative.benchmark_return = last / initial - 1.0;
+        }
+
+        let returns = daily_returns(equity, tz);
+        let benchmarks = daily_returns(benchmark, tz);
+        let count = returns.len().min(benchmarks.len());
+        if count < 2 {
+            return relative;
+        }
+        let (returns, benchmarks) = (&returns[..count], &benchmarks[..count]);
+
+        let (mean, std) = mean_std(returns);
+        let (benchmark_mean, benchmark_std) = mean_std(benchmarks);
+        let covariance = returns
+            .iter()
+            .zip(benchmarks)
+            .map(|(value, benchmark)| (value - mean) * (benchmark - benchmark_mean))
+            .sum::<f64>()
+            / (count - 1) as f64;
+
+        if benchmark_std > 0.0 {
+            relative.beta = covariance / benchmark_std.powi(2);
+            if std > 0.0 {
+                relative.correlation = covariance / (std * benchmark_std);
+            }
+        }
+        relative.alpha = (mean - relative.beta * benchmark_mean) * DAYS_PER_YEAR;
+
+        let excess = returns
+            .iter()
+            .zip(benchmarks)
+            .map(|(value, benchmark)| value - benchmark)
+            .collect::<Vec<_>>();
+        let (excess_mean, excess_std) = mean_std(&excess);
+        relative.tracking_error = excess_std * DAYS_PER_YEAR.sqrt();
+        if excess_std > 0.0 {
+            relative.information_ratio = excess_mean / excess_std * DAYS_PER_YEAR.sqrt();
+        }
+
+        relative
+    }
+
+    pub fn to_columns(&self) -> Vec<Column> {
+        vec![
+            Column::new("benchmark_return".into(), vec![self.benchmark_return]),
+            Column::new("alpha".into(), vec![self.alpha]),
+            Column::new("beta".into(), vec![self.beta]),
+            Column::new("correlation".into(), vec![self.correlation]),
+            Column::new("tracking_error".into(), vec![self.tracking_error]),
+            Column::new("information_ratio".into(), vec![self.information_ratio]),
+        ]
+    }
+}
+
+/// 按时区的自然日取收盘权益计算日收益率, 与报告的月度收益使用相同的日期边界
+pub fn daily_returns(equity: &Equity, tz: &Tz) -> Vec<f64> {
+    let mut closes: Vec<f64> = vec![];
+    let mut current = None;
+    for (time, value) in equity {
+        let day = tz.timestamp_nanos(*time).date_naive();
+        if current == Some(day) {
+            *closes.last_mut().unwrap() = *value;
+        } else {
+            closes.push(*value);
+            current = Some(day);
+        }
+    }
+    closes
+        .windows(2)
+        .filter(|pair| pair[0] != 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect()
+}
+
+pub fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.len() < 2 {
+        return (values.first().copied().unwrap_or_default(), 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var.sqrt())
+}
+
+pub fn max_drawdown(equity: &Equity) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown = 0.0f64;
+    for (_, value) in equity {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak);
+        }
+    }
+    drawdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::constants::DEFAULT_TZ,
+        types::{
+            base::{Codes, Direction, Method, OrderStatus, Side},
+            order::OrderData,
+        },
+    };
+    use chrono_tz::UTC;
+    use rust_decimal::dec;
+
+    const HOUR_NANOS: i64 = DAY_NANOS / 24;
+
+    fn order(deal_size: Decimal, deal_fee: Decimal) -> Order {
+        let time = DEFAULT_TZ.timestamp_nanos(0);
+        Order::from(OrderData {
+            code: Codes::BTC_SWAP,
+            id: "order".to_string(),
+            method: Method::Market,
+            direction: Direction::Long,
+            side: Side::Buy,
+            status: OrderStatus::Completed,
+            size: dec!(1),
+            price: Decimal::ZERO,
+            deal_size,
+            deal_price: dec!(100),
+            deal_fee,
+            margin: Decimal::ZERO,
+            remark: None,
+            create_time: time,
+            update_time: time,
+        })
+    }
+
+    #[test]
+    fn daily_returns_follow_tz() {
+        let equity = [(0, 100.0), (20 * HOUR_NANOS, 110.0), (DAY_NANOS, 121.0)];
+        let returns = daily_returns(&equity, &UTC);
+        assert_eq!(returns.len(), 1);
+        assert!((returns[0] - 0.1).abs() < 1e-12);
+
+        // 东八区20:00 UTC已是次日
+        let returns = daily_returns(&equity, &DEFAULT_TZ);
+        assert_eq!(returns.len(), 1);
+        assert!((returns[0] - 0.21).abs() < 1e-12);
+    }
+
+    #[test]
+    fn compute_metrics() {
+        let equity = [
+            (0, 1000.0),
+            (12 * HOUR_NANOS, 1100.0),
+            (DAY_NANOS, 1200.0),
+            (DAY_NANOS + 12 * HOUR_NANOS, 900.0),
+            (2 * DAY_NANOS, 990.0),
+        ];
+        let orders = [
+            order(dec!(1), dec!(0.5)),
+            order(Decimal::ZERO, Decimal::ZERO),
+        ];
+        let metrics = Metrics::compute(&equity, &orders, &UTC);
+
+        assert_eq!(metrics.final_equity, 990.0);
+        assert!((metrics.total_return + 0.01).abs() < 1e-12);
+        assert!((metrics.annual_return - (0.99f64.powf(DAYS_PER_YEAR / 2.0) - 1.0)).abs() < 1e-12);
+        assert!((metrics.max_drawdown - 0.25).abs() < 1e-12);
+        assert_eq!((metrics.orders, metrics.fills), (2, 1));
+        assert_eq!(metrics.fees, 0.5);
+
+        let (mean, std) = mean_std(&daily_returns(&equity, &UTC));
+        assert!(std > 0.0);
+        assert!((metrics.sharpe - mean / std * DAYS_PER_YEAR.sqrt()).abs() < 1e-12);
+        assert_eq!(metrics.get("fills"), Some(1.0));
+        assert_eq!(metrics.get("unknown"), None);
+    }
+
+    #[test]
+    fn compute_empty() {
+        let metrics = Metrics::compute(&[], &[], &UTC);
+        assert_eq!(metrics.final_equity, 0.0);
+        assert_eq!(metrics.max_drawdown, 0.0);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    backtest::Backtest,
+    metrics::{Equity, Metrics},
+    types::{base::Resample, order::Order},
+};
+use anyhow::{Result, ensure};
+use polars::prelude::{Column, DataFrame};
+use pyo3::pymethods;
+use pyo3_polars::PyDataFrame;
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha12Rng;
+use rayon::prelude::*;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+#[pymethods]
+impl Backtest {
+    /// 蒙特卡洛稳健性分析: 对回测结果重采样, 返回每次模拟的指标和置信区间
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (method=Resample::Shuffle, runs=1000, block="1d", fee_jitter=0.5, slippage=0.0005, confidence=0.95, seed=None))]
+    fn monte_carlo(
+        &self,
+        method: Resample,
+        runs: usize,
+        block: &str,
+        fee_jitter: f64,
+        slippage: f64,
+        confidence: f64,
+        seed: Option<u64>,
+    ) -> Result<(PyDataFrame, PyDataFrame)> {
+        let equity = self.equity().clone();
+        let tz = *self.context().tz();
+        ensure!(equity.len() > 1, "没有权益曲线, 请先运行回测");
+        ensure!(runs > 0, "模拟次数错误: {runs}");
+        ensure!(
+            confidence > 0.0 && confidence < 1.0,
+            "置信度错误: {confidence}"
+        );
+        ensure!(
+            (0.0..=1.0).contains(&fee_jitter),
+            "手续费扰动比例错误: {fee_jitter}"
+        );
+        ensure!(slippage >= 0.0, "滑点错误: {slippage}");
+        let block = (crate::helpers::time::parse_duration(block)?.num_minutes() as usize).max(1);
+
+        let fills = fills(&equity, &self.orders());
+        let returns = bar_returns(&equity);
+        let segments = segments(&returns, &fills);
+
+        // 默认从引擎的随机数生成器取种子; 每次模拟使用独立的种子, 结果与并行调度无关
+        let seed = seed.unwrap_or_else(|| self.rng_mut().random::<u64>());
+        let samples = (0..runs)
+            .into_par_iter()
+            .map(|run| {
+                let mut rng = ChaCha12Rng::seed_from_u64(seed.wrapping_add(run as u64));
+                let path = match method {
+                    Resample::Shuffle => {
+                        let mut segments = segments.clone();
+                        segments.shuffle(&mut rng);
+                        rebuild(&equity, &segments.concat())
+                    }
+                    Resample::Bootstrap => rebuild(&equity, &bootstrap(&returns, block, &mut rng)),
+                    Resample::Perturb => perturb(&equity, &fills, fee_jitter, slippage, &mut rng),
+                };
+                Metrics::compute(&path, &[], &tz)
+            })
+            .collect::<Vec<_>>();
+
+        let actual = Metrics::compute(&equity, &[], &tz);
+        let names = ["final_equity", "max_drawdown", "sharpe"];
+        let lower = (1.0 - confidence) / 2.0;
+
+        let mut columns = vec![Column::new(
+            "run".into(),
+            (0..runs as u32).collect::<Vec<_>>(),
+        )];
+        let mut summary = vec![];
+        for name in names {
+            let mut values = samples
+                .iter()
+                .map(|item| item.get(name).unwrap_or_default())
+                .collect::<Vec<_>>();
+            columns.push(Column::new(name.into(), values.clone()));
+
+            values.sort_by(f64::total_cmp);
+            let (mean, std) = crate::metrics::mean_std(&values);
+            summary.push([
+                actual.get(name).unwrap_or_default(),
+                mean,
+                std,
+                quantile(&values, lower),
+                quantile(&values, 0.5),
+                quantile(&values, 1.0 - lower),
+            ]);
+        }
+
+        let stats = ["actual", "mean", "std", "lower", "median", "upper"];
+        let mut summary_columns = vec![Column::new("metric".into(), names.to_vec())];
+        for (index, stat) in stats.iter().enumerate() {
+            summary_columns.push(Column::new(
+                (*stat).into(),
+                summary.iter().map(|row| row[index]).collect::<Vec<_>>(),
+            ));
+        }
+
+        Ok((
+            PyDataFrame(DataFrame::new(columns)?),
+            PyDataFrame(DataFrame::new(summary_columns)?),
+        ))
+    }
+}
+
+/// 成交: (权益曲线下标, 手续费, 成交额)
+type Fill = (usize, f64, f64);
+
+/// 有成交的订单, 按最后成交时间对应到权益曲线
+fn fills(equity: &Equity, orders: &[Order]) -> Vec<Fill> {
+    let mut fills = orders
+        .iter()
+        .filter(|order| *order.deal_size() > Decimal::ZERO)
+        .map(|order| {
+            let time = order
+                .update_time()
+                .timestamp_nanos_opt()
+                .unwrap_or_default();
+            (
+                equity.partition_point(|(item, _)| *item < time),
+                order.deal_fee().to_f64().unwrap_or_default(),
+                (*order.deal_size() * *order.deal_price())
+                    .to_f64()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    fills.sort_by_key(|fill| fill.0);
+    fills
+}
+
+/// 每分钟收益率
+fn bar_returns(equity: &Equity) -> Vec<f64> {
+    equity
+        .windows(2)
+        .map(|pair| {
+            if pair[0].1 != 0.0 {
+                pair[1].1 / pair[0].1 - 1.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// 按成交把收益率切分为交易段, 每段从一次成交开始到下一次成交之前
+fn segments(returns: &[f64], fills: &[Fill]) -> Vec<Vec<f64>> {
+    let mut bounds = fills
+        .iter()
+        .map(|(index, ..)| index.saturating_sub(1).min(returns.len()))
+        .collect::<Vec<_>>();
+    bounds.push(returns.len());
+    bounds.dedup();
+
+    let mut start = 0;
+    let mut segments = vec![];
+    for bound in bounds {
+        if bound > start {
+            segments.push(returns[start..bound].to_vec());
+            start = bound;
+        }
+    }
+    segments
+}
+
+/// 分块自助法: 随机抽取连续的收益率块拼接到原长度, 保留块内的自相关
+fn bootstrap(returns: &[f64], block: usize, rng: &mut ChaCha12Rng) -> Vec<f64> {
+    let block = block.min(returns.len());
+    let mut sample = Vec::with_capacity(returns.len() + block);
+    while sample.len() < returns.len() {
+        let start = rng.random_range(0..=returns.len() - block);
+        sample.extend_from_slice(&returns[start..start + block]);
+    }
+    sample.truncate(returns.len());
+    sample
+}
+
+/// 按收益率从初始权益重建权益曲线, 沿用原始时间
+fn rebuild(equity: &Equity, returns: &[f64]) -> Vec<(i64, f64)> {
+    let mut value = equity[0].1;
+    std::iter::once(equity[0])
+        .chain(equity[1..].iter().zip(returns).map(|((time, _), item)| {
+            value *= 1.0 + item;
+            (*time, value)
+        }))
+        .collect()
+}
+
+/// 扰动交易成本: 手续费按[1-fee_jitter, 1+fee_jitter]倍随机缩放, 另加[0, slippage]比例的随机滑点, 从成交时起扣减权益
+fn perturb(
+    equity: &Equity,
+    fills: &[Fill],
+    fee_jitter: f64,
+    slippage: f64,
+    rng: &mut ChaCha12Rng,
+) -> Vec<(i64, f64)> {
+    let mut costs = vec![0.0; equity.len()];
+    for (index, fee, value) in fills {
+        let Some(cost) = costs.get_mut(*index) else {
+            continue;
+        };
+        *cost += fee * rng.random_range(-fee_jitter..=fee_jitter)
+            + value * rng.random_range(0.0..=slippage);
+    }
+
+    let mut total = 0.0;
+    equity
+        .iter()
+        .zip(costs)
+        .map(|((time, value), cost)| {
+            total += cost;
+            (*time, value - total)
+        })
+        .collect()
+}
+
+/// 线性插值分位数, values需已排序
+fn quantile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let position = q * (values.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    values[low] + (values[high] - values[low]) * (position - low as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantile_interpolates() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(quantile(&values, 0.0), 1.0);
+        assert_eq!(quantile(&values, 0.5), 3.0);
+        assert_eq!(quantile(&values, 1.0), 5.0);
+        assert!((quantile(&values, 0.1) - 1.4).abs() < 1e-12);
+        assert!((quantile(&values, 0.95) - 4.8).abs() < 1e-12);
+        assert_eq!(quantile(&[7.0], 0.3), 7.0);
+        assert_eq!(quantile(&[], 0.5), 0.0);
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{backtest::Backtest, metrics::Metrics, types::alias::Time};
+use anyhow::{Result, anyhow, ensure};
+use chrono::{Duration, DurationRound};
+use chrono_tz::Tz;
+use polars::prelude::{Column, DataFrame, DataType, TimeUnit, TimeZone};
+use pyo3::{
+    Bound, Py, PyAny, Python, pymethods,
+    types::{PyAnyMethods, PyBool, PyDict, PyDictMethods, PyType},
+};
+use pyo3_polars::PyDataFrame;
+use rayon::prelude::*;
+
+/// 每个回测单独写文件的参数
+const PER_RUN_OPTIONS: [&str; 3] = ["checkpoint", "results_dir", "save_results"];
+
+#[pymethods]
+impl Backtest {
+    /// 参数扫描: 按参数网格创建策略和回测, K线只加载一次, 并行回测
+    #[classmethod]
+    #[pyo3(signature = (strategy_factory, param_grid, begin, end, symbols, **kwargs))]
+    fn sweep(
+        cls: &Bound<PyType>,
+        strategy_factory: &Bound<PyAny>,
+        param_grid: &Bound<PyDict>,
+        begin: &str,
+        end: &str,
+        symbols: &Bound<PyAny>,
+        kwargs: Option<&Bound<PyDict>>,
+    ) -> Result<PyDataFrame> {
+        let py = cls.py();
+        let grid = ParamGrid::new(param_grid)?;
+
+        let mut backtests = vec![];
+        for index in 0..grid.combos.len() {
+            let params = grid.params(py, index)?;
+            backtests.push(build(
+                cls,
+                strategy_factory,
+                &params,
+                begin,
+                end,
+                symbols,
+                kwargs,
+            )?);
+        }
+
+        crate::helpers::log::init(Some(1024));
+        let results = simulate_all(py, &backtests)?;
+        crate::helpers::log::flush()?;
+
+        let mut columns = grid.columns(py, &(0..grid.combos.len()).collect::<Vec<_>>())?;
+        columns.push(Column::new(
+            "seed".into(),
+            backtests
+                .iter()
+                .map(|backtest| backtest.config().seed)
+                .collect::<Vec<_>>(),
+        ));
+        columns.extend(Metrics::to_columns(
+            &results
+                .iter()
+                .map(|result| result.clone().unwrap_or_default())
+                .collect::<Vec<_>>(),
+        ));
+        columns.push(Column::new(
+            "error".into(),
+            results
+                .iter()
+                .map(|result| result.as_ref().err().cloned())
+                .collect::<Vec<_>>(),
+        ));
+
+        Ok(PyDataFrame(DataFrame::new(columns)?))
+    }
+
+    /// 滚动优化: 每个窗口在样本内扫描参数, 按指标选出最优参数后在样本外回测, 拼接样本外权益曲线
+    #[allow(clippy::too_many_arguments)]
+    #[classmethod]
+    #[pyo3(signature = (strategy_factory, param_grid, begin, end, symbols, in_sample="90d", out_sample="30d", anchored=false, metric="sharpe", maximize=true, **kwargs))]
+    fn walk_forward(
+        cls: &Bound<PyType>,
+        strategy_factory: &Bound<PyAny>,
+        param_grid: &Bound<PyDict>,
+        begin: &str,
+        end: &str,
+        symbols: &Bound<PyAny>,
+        in_sample: &str,
+        out_sample: &str,
+        anchored: bool,
+        metric: &str,
+        maximize: bool,
+        kwargs: Option<&Bound<PyDict>>,
+    ) -> Result<(PyDataFrame, PyDataFrame)> {
+        let py = cls.py();
+        let grid = ParamGrid::new(param_grid)?;
+        ensure!(
+            Metrics::default().get(metric).is_some(),
+            "未知指标: {metric}"
+        );
+
+        let tz = match kwargs
+            .map(|kwargs| kwargs.get_item("tz"))
+            .transpose()?
+            .flatten()
+        {
+            Some(tz) => crate::helpers::time::parse_tz(&tz.extract::<String>()?)?,
+            None => crate::helpers::constants::DEFAULT_TZ,
+        };
+        let begin =
+            crate::helpers::time::str_to_time(begin, &tz)?.duration_trunc(Duration::minutes(1))?;
+        let end =
+            crate::helpers::time::str_to_time(end, &tz)?.duration_trunc(Duration::minutes(1))?;
+        ensure!(begin < end, "开始时间不能大于结束时间: {begin} - {end}");
+
+        let in_sample = crate::helpers::time::parse_duration(in_sample)?;
+        let out_sample = crate::helpers::time::parse_duration(out_sample)?;
+        ensure!(
+            in_sample >= Duration::minutes(1),
+            "样本内区间错误: {in_sample}"
+        );
+        ensure!(
+            out_sample >= Duration::minutes(1),
+            "样本外区间错误: {out_sample}"
+        );
+
+        let windows = windows(begin, end, in_sample, out_sample, anchored);
+        ensure!(
+            !windows.is_empty(),
+            "回测区间不足一个样本内区间: {begin} - {end}"
+        );
+
+        crate::helpers::log::init(Some(1024));
+
+        let mut bests = vec![];
+        let mut seeds = vec![];
+        let mut scores = vec![];
+        let mut results = vec![];
+        let mut equity: Vec<(i64, f64)> = vec![];
+        let mut equity_windows = vec![];
+        for (window, (is_begin, is_end, oos_end)) in windows.iter().enumerate() {
+            let mut backtests = vec![];
+            for index in 0..grid.combos.len() {
+                let params = grid.params(py, index)?;
+                backtests.push(build(
+                    cls,
+                    strategy_factory,
+                    &params,
+                    &is_begin.to_rfc3339(),
+                    &is_end.to_rfc3339(),
+                    symbols,
+                    kwargs,
+                )?);
+            }
+
+            let (best, score) = simulate_all(py, &backtests)?
+                .iter()
+                .enumerate()
+                .filter_map(|(index, result)| Some((index, result.as_ref().ok()?.get(metric)?)))
+                .filter(|(_, score)| !score.is_nan())
+                .max_by(|(_, a), (_, b)| {
+                    if maximize {
+                        a.total_cmp(b)
+                    } else {
+                        b.total_cmp(a)
+                    }
+                })
+                .ok_or(anyhow!("样本内回测全部失败: 窗口={window}"))?;
+
+            let params = grid.params(py, best)?;
+            let backtest = build(
+                cls,
+                strategy_factory,
+                &params,
+                &is_end.to_rfc3339(),
+                &oos_end.to_rfc3339(),
+                symbols,
+                kwargs,
+            )?;
+            let result = simulate_all(py, std::slice::from_ref(&backtest))?
+                .remove(0)
+                .map_err(|err| anyhow!("样本外回测失败: 窗口={window}, {err}"))?;
+
+            // 每个窗口的权益按上一个窗口的期末权益缩放, 窗口边界重复的分钟只保留一次
+            let curve = backtest.equity().clone();
+            if let Some((_, first)) = curve.first() {
+                let scale = match equity.last() {
+                    Some((_, last)) if *first != 0.0 => last / first,
+                    _ => 1.0,
+                };
+                for (time, value) in curve.iter() {
+                    if equity.last().is_some_and(|(last, _)| time <= last) {
+                        continue;
+                    }
+                    equity.push((*time, value * scale));
+                    equity_windows.push(window as u32);
+                }
+            }
+
+            seeds.push(backtest.config().seed);
+            bests.push(best);
+            scores.push(score);
+            results.push(result);
+        }
+
+        crate::helpers::log::flush()?;
+
+        let nanos = |times: Vec<Time>| {
+            times
+                .iter()
+                .map(|time| time.timestamp_nanos_opt().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        let mut columns = vec![
+            Column::new(
+                "window".into(),
+                (0..windows.len() as u32).collect::<Vec<_>>(),
+            ),
+            time_column(
+                "is_begin",
+                nanos(windows.iter().map(|item| item.0).collect()),
+                &tz,
+            )?,
+            time_column(
+                "is_end",
+                nanos(windows.iter().map(|item| item.1).collect()),
+                &tz,
+            )?,
+            time_column(
+                "oos_begin",
+                nanos(windows.iter().map(|item| item.1).collect()),
+                &tz,
+            )?,
+            time_column(
+                "oos_end",
+                nanos(windows.iter().map(|item| item.2).collect()),
+                &tz,
+            )?,
+        ];
+        columns.extend(grid.columns(py, &bests)?);
+        columns.push(Column::new(format!("is_{metric}").into(), scores));
+        columns.push(Column::new("seed".into(), seeds));
+        columns.extend(Metrics::to_columns(&results));
+
+        let equity_df = DataFrame::new(vec![
+            time_column("time", equity.iter().map(|(time, _)| *time).collect(), &tz)?,
+            Column::new(
+                "equity".into(),
+                equity.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+            ),
+            Column::new("window".into(), equity_windows),
+        ])?;
+
+        Ok((
+            PyDataFrame(DataFrame::new(columns)?),
+            PyDataFrame(equity_df),
+        ))
+    }
+}
+
+/// 参数网格, 按笛卡尔积展开, 每组参数为各参数候选值的下标
+struct ParamGrid {
+    names: Vec<String>,
+    choices: Vec<Vec<Py<PyAny>>>,
+    combos: Vec<Vec<usize>>,
+}
+
+impl ParamGrid {
+    fn new(grid: &Bound<PyDict>) -> Result<Self> {
+        let mut names = vec![];
+        let mut choices = vec![];
+        for (name, values) in grid.iter() {
+            names.push(name.extract::<String>()?);
+            choices.push(
+                values
+                    .try_iter()?
+                    .map(|value| value.map(|value| value.unbind()))
+                    .collect::<pyo3::PyResult<Vec<Py<PyAny>>>>()?,
+            );
+        }
+        let combos = cartesian(&choices.iter().map(|item| item.len()).collect::<Vec<_>>());
+        ensure!(!combos.is_empty(), "参数网格为空");
+        Ok(Self {
+            names,
+            choices,
+            combos,
+        })
+    }
+
+    /// 第index组参数, 作为策略工厂的关键字参数
+    fn params<'py>(&self, py: Python<'py>, index: usize) -> Result<Bound<'py, PyDict>> {
+        let params = PyDict::new(py);
+        for (position, choice) in self.combos[index].iter().enumerate() {
+            params.set_item(
+                &self.names[position],
+                self.choices[position][*choice].bind(py),
+            )?;
+        }
+        Ok(params)
+    }
+
+    /// 参数列, 每组参数一行
+    fn columns(&self, py: Python, indexes: &[usize]) -> Result<Vec<Column>> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(position, name)| {
+                let values = indexes
+                    .iter()
+                    .map(|index| {
+                        self.choices[position][self.combos[*index][position]]
+                            .bind(py)
+                            .clone()
+                    })
+                    .collect::<Vec<_>>();
+                param_column(name, &values)
+            })
+            .collect()
+    }
+}
+
+/// 以参数调用策略工厂, 并按其它参数创建回测
+/// - 所有回测共享其它参数, 不支持写文件的参数, 否则并行回测会同时写同一个文件
+/// - 参数扫描的回测不保存结果
+fn build(
+    cls: &Bound<PyType>,
+    strategy_factory: &Bound<PyAny>,
+    params: &Bound<PyDict>,
+    begin: &str,
+    end: &str,
+    symbols: &Bound<PyAny>,
+    kwargs: Option<&Bound<PyDict>>,
+) -> Result<Backtest> {
+    let options = match kwargs {
+        Some(kwargs) => {
+            for key in PER_RUN_OPTIONS {
+                ensure!(!kwargs.contains(key)?, "参数扫描不支持参数: {key}");
+            }
+            kwargs.copy()?
+        }
+        None => PyDict::new(cls.py()),
+    };
+    options.set_item("save_results", false)?;
+
+    let strategy = strategy_factory.call((), Some(params))?;
+    Ok(cls
+        .call((strategy, begin, end, symbols), Some(&options))?
+        .extract::<Backtest>()?)
+}
+
+/// 由第一个回测同步和加载K线, 所有回测共享并行执行, 返回每个回测的指标或失败原因
+fn simulate_all(py: Python, backtests: &[Backtest]) -> Result<Vec<Result<Metrics, String>>> {
+    let first = &backtests[0];
+    let codes = first.sync_data()?;
+    let frames = first.load_frames(&codes)?;
+
+    Ok(py.allow_threads(|| {
+        backtests
+            .par_iter()
+            .map(|backtest| {
+                backtest
+                    .simulate(&codes, &frames)
+                    .map(|_| backtest.metrics())
+                    .map_err(|err| err.to_string())
+            })
+            .collect()
+    }))
+}
+
+/// 滚动窗口: (样本内开始, 样本内结束即样本外开始, 样本外结束)
+/// - 滚动: 样本内区间长度固定, 随窗口向后移动
+/// - 锚定: 样本内区间始终从开始时间起算
+fn windows(
+    begin: Time,
+    end: Time,
+    in_sample: Duration,
+    out_sample: Duration,
+    anchored: bool,
+) -> Vec<(Time, Time, Time)> {
+    let mut windows = vec![];
+    let mut split = begin + in_sample;
+    while split < end {
+        let is_begin = if anchored { begin } else { split - in_sample };
+        windows.push((is_begin, split, (split + out_sample).min(end)));
+        split += out_sample;
+    }
+    windows
+}
+
+fn time_column(name: &str, nanos: Vec<i64>, tz: &Tz) -> Result<Column> {
+    Ok(Column::new(name.into(), nanos).cast(&DataType::Datetime(
+        TimeUnit::Nanoseconds,
+        Some(TimeZone::from_chrono(tz)),
+    ))?)
+}
+
+/// 参数网格的笛卡尔积, 返回每组参数的下标
+fn cartesian(sizes: &[usize]) -> Vec<Vec<usize>> {
+    sizes.iter().fold(vec![vec![]], |combos, size| {
+        combos
+            .into_iter()
+            .flat_map(|combo| {
+                (0..*size).map(move |index| {
+                    let mut combo = combo.clone();
+                    combo.push(index);
+                    combo
+                })
+            })
+            .collect()
+    })
+}
+
+/// 参数列, 按取值类型推断列类型, 无法识别时转为字符串
+fn param_column(name: &str, values: &[Bound<PyAny>]) -> Result<Column> {
+    if values.iter().all(|value| value.is_instance_of::<PyBool>()) {
+        let values = values
+            .iter()
+            .map(|value| value.extract::<bool>())
+            .collect::<pyo3::PyResult<Vec<_>>>()?;
+        return Ok(Column::new(name.into(), values));
+    }
+    if let Ok(values) = values
+        .iter()
+        .map(|value| value.extract::<i64>())
+        .collect::<pyo3::PyResult<Vec<_>>>()
+    {
+        return Ok(Column::new(name.into(), values));
+    }
+    if let Ok(values) = values
+        .iter()
+        .map(|value| value.extract::<f64>())
+        .collect::<pyo3::PyResult<Vec<_>>>()
+    {
+        return Ok(Column::new(name.into(), values));
+    }
+    let values = values
+        .iter()
+        .map(|value| Ok(value.str()?.to_string()))
+        .collect::<pyo3::PyResult<Vec<_>>>()?;
+    Ok(Column::new(name.into(), values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::constants::DEFAULT_TZ;
+    use chrono::TimeZone as _;
+
+    fn day(d: u32) -> Time {
+        DEFAULT_TZ.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn rolling_and_anchored_windows() {
+        let (in_sample, out_sample) = (Duration::days(10), Duration::days(5));
+
+        let rolling = windows(day(1), day(26), in_sample, out_sample, false);
+        assert_eq!(
+            rolling,
+            vec![
+                (day(1), day(11), day(16)),
+                (day(6), day(16), day(21)),
+                (day(11), day(21), day(26)),
+            ]
+        );
+
+        // 最后一个样本外区间截止到结束时间
+        let anchored = windows(day(1), day(23), in_sample, out_sample, true);
+        assert_eq!(
+            anchored,
+            vec![
+                (day(1), day(11), day(16)),
+                (day(1), day(16), day(21)),
+                (day(1), day(21), day(23)),
+            ]
+        );
+
+        assert!(windows(day(1), day(11), in_sample, out_sample, false).is_empty());
+    }
+
+    #[test]
+    fn cartesian_indices() {
+        assert_eq!(
+            cartesian(&[2, 3]),
+            vec![
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 0],
+                vec![1, 1],
+                vec![1, 2],
+            ]
+        );
+        assert_eq!(cartesian(&[]), vec![Vec::<usize>::new()]);
+        assert!(cartesian(&[2, 0]).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    providers::{DataProvider, normalize},
+    types::{
+        alias::Time,
+        base::{Codes, Interval},
+    },
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use polars::prelude::*;
+
+pub struct FixtureProvider;
+
+#[async_trait]
+impl DataProvider for FixtureProvider {
+    fn name(&self) -> &str {
+        "fixture"
+    }
+
+    async fn fetch(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Time,
+        end: Time,
+    ) -> Result<DataFrame> {
+        let time = date_range(
+            "time".into(),
+            begin.naive_utc(),
+            end.naive_utc(),
+            Duration::parse(interval.abbr()),
+            ClosedWindow::Both,
+            TimeUnit::Nanoseconds,
+            Some(&begin.timezone()),
+        )?
+        .into_column();
+
+        let len = time.len();
+        let mut seed = (code as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut price = 100.0;
+        let mut opens = Vec::with_capacity(len);
+        let mut highs = Vec::with_capacity(len);
+        let mut lows = Vec::with_capacity(len);
+        let mut closes = Vec::with_capacity(len);
+        let mut volumes = Vec::with_capacity(len);
+        for _ in 0..len {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let change = (seed % 2001) as f64 / 1000.0 - 1.0;
+
+            let open = price;
+            let close = (open * (1.0 + change * 0.002)).max(0.01);
+            opens.push(open);
+            highs.push(open.max(close) * 1.0005);
+            lows.push(open.min(close) * 0.9995);
+            closes.push(close);
+            volumes.push((seed % 1000) as f64 + 1.0);
+            price = close;
+        }
+
+        let df = DataFrame::new(vec![
+            time,
+            Column::new("open".into(), opens),
+            Column::new("high".into(), highs),
+            Column::new("low".into(), lows),
+            Column::new("close".into(), closes),
+            Column::new("volume".into(), volumes),
+        ])?;
+
+        normalize(df.lazy(), &begin.timezone())
+    }
+}
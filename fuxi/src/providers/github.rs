@@ -0,0 +1,70 @@
+use crate::{
+    data_store::DataStore,
+    providers::{DataProvider, filter_range, normalize},
+    types::{
+        alias::Time,
+        base::{Codes, Interval, Market},
+    },
+};
+use anyhow::{Context as _, Result, ensure};
+use async_trait::async_trait;
+use polars::prelude::*;
+use std::{io::Cursor, time::Duration};
+
+const DOWNLOAD_PREFIX: &str = "https://raw.githubusercontent.com/FrequentHippos/freqtrade_hyperliquid_download-data/refs/heads/main/user_data/data/hyperliquid/";
+
+pub struct GithubProvider {
+    client: reqwest::Client,
+}
+
+impl GithubProvider {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl DataProvider for GithubProvider {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    async fn fetch(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Time,
+        end: Time,
+    ) -> Result<DataFrame> {
+        ensure!(
+            interval == Interval::Min,
+            "数据源不支持该周期 交易对: {code}, 周期: {interval}"
+        );
+
+        let download_path = format!(
+            "{DOWNLOAD_PREFIX}{}",
+            match code.market() {
+                Market::Spot => format!("{}-1m.feather", DataStore::file_stem(code)),
+                Market::Swap =>
+                    format!("futures/{}-1m-futures.feather", DataStore::file_stem(code)),
+            }
+        );
+
+        let response = self
+            .client
+            .get(download_path)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("下载失败 交易对: {code}"))?;
+        let bytes = response.bytes().await?;
+        let tz = begin.timezone();
+
+        tokio::task::spawn_blocking(move || {
+            let df = IpcReader::new(Cursor::new(bytes)).finish()?;
+            filter_range(normalize(df.lazy(), &tz)?, begin, end)
+        })
+        .await?
+    }
+}
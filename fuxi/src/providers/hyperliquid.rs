@@ -0,0 +1,120 @@
+use crate::{
+    providers::{DataProvider, normalize},
+    types::{
+        alias::Time,
+        base::{Codes, Interval},
+    },
+};
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use polars::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+const INFO_URL: &str = "https://api.hyperliquid.xyz/info";
+const MAX_CANDLES: usize = 5000;
+
+#[derive(Deserialize)]
+struct CandleSnapshot {
+    t: i64,
+    #[serde(rename = "T")]
+    close_time: i64,
+    o: String,
+    h: String,
+    l: String,
+    c: String,
+    v: String,
+}
+
+pub struct HyperliquidProvider {
+    client: reqwest::Client,
+}
+
+impl HyperliquidProvider {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl DataProvider for HyperliquidProvider {
+    fn name(&self) -> &str {
+        "hyperliquid"
+    }
+
+    async fn fetch(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Time,
+        end: Time,
+    ) -> Result<DataFrame> {
+        let interval_name = match interval {
+            Interval::Month => "1M",
+            interval => interval.abbr(),
+        };
+
+        let mut times = vec![];
+        let mut opens = vec![];
+        let mut highs = vec![];
+        let mut lows = vec![];
+        let mut closes = vec![];
+        let mut volumes = vec![];
+
+        let tz = begin.timezone();
+        let end = end.timestamp_millis();
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut start = begin.timestamp_millis();
+        while start <= end {
+            let response = self
+                .client
+                .post(INFO_URL)
+                .json(&json!({
+                    "type": "candleSnapshot",
+                    "req": {
+                        "coin": code.id(),
+                        "interval": interval_name,
+                        "startTime": start,
+                        "endTime": end,
+                    }
+                }))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .with_context(|| format!("下载失败 交易对: {code}"))?;
+
+            let candles = response.json::<Vec<CandleSnapshot>>().await?;
+            let Some(last) = candles.last().map(|candle| candle.t) else {
+                break;
+            };
+            let count = candles.len();
+            // 最后一根K线可能还未收盘, 增量同步从已有数据之后开始, 写入后不会再更新
+            for candle in candles.into_iter().filter(|candle| candle.close_time < now) {
+                times.push(candle.t);
+                opens.push(candle.o.parse::<f64>()?);
+                highs.push(candle.h.parse::<f64>()?);
+                lows.push(candle.l.parse::<f64>()?);
+                closes.push(candle.c.parse::<f64>()?);
+                volumes.push(candle.v.parse::<f64>()?);
+            }
+
+            if count < MAX_CANDLES {
+                break;
+            }
+            start = last + 1;
+        }
+
+        let df = df!(
+            "time" => times,
+            "open" => opens,
+            "high" => highs,
+            "low" => lows,
+            "close" => closes,
+            "volume" => volumes,
+        )?;
+
+        normalize(df.lazy(), &tz)
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    data_store::{DataStore, scan_file},
+    providers::{DataProvider, filter_range, normalize},
+    types::{
+        alias::Time,
+        base::{Codes, DataFormat, Interval, Market},
+    },
+};
+use anyhow::{Result, anyhow, ensure};
+use async_trait::async_trait;
+use polars::prelude::*;
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+const EXTENSIONS: [&str; 5] = ["feather", "arrow", "ipc", "parquet", "csv"];
+
+/// 本地数据源: 数据目录或zip压缩包, 压缩包内的文件按相同的相对路径查找
+pub struct LocalProvider {
+    path: PathBuf,
+    archive: bool,
+}
+
+impl LocalProvider {
+    pub fn new(path: &str) -> Result<Self> {
+        let path = PathBuf::from(path);
+        let archive = path.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        ensure!(
+            path.is_dir() || archive,
+            "本地数据目录或zip压缩包不存在: {}",
+            path.display()
+        );
+        Ok(Self { path, archive })
+    }
+
+    /// 候选的相对路径, 按优先级排序
+    fn candidates(code: Codes, interval: Interval) -> Vec<String> {
+        let stem = DataStore::file_stem(code);
+        let mut names = vec![format!("{stem}-{}", interval.abbr())];
+        if code.market() == Market::Swap {
+            names.push(format!("{stem}-{}-futures", interval.abbr()));
+        }
+        if interval == Interval::Min {
+            names.push(stem);
+        }
+
+        let dirs = [
+            "",
+            match code.market() {
+                Market::Spot => "spot/",
+                Market::Swap => "swap/",
+            },
+            "futures/",
+        ];
+
+        dirs.iter()
+            .flat_map(|dir| {
+                names.iter().flat_map(move |name| {
+                    EXTENSIONS
+                        .iter()
+                        .map(move |ext| format!("{dir}{name}.{ext}"))
+                })
+            })
+            .collect()
+    }
+
+    fn find(&self, code: Codes, interval: Interval) -> Option<PathBuf> {
+        Self::candidates(code, interval)
+            .into_iter()
+            .map(|name| self.path.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// 读取压缩包内匹配的文件, 允许压缩包内多一层根目录
+    fn read_archive(path: &Path, code: Codes, interval: Interval) -> Result<Option<DataFrame>> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let names = archive.file_names().map(str::to_owned).collect::<Vec<_>>();
+        let Some(name) = Self::candidates(code, interval)
+            .into_iter()
+            .find_map(|candidate| {
+                names
+                    .iter()
+                    .find(|name| **name == candidate || name.ends_with(&format!("/{candidate}")))
+                    .cloned()
+            })
+        else {
+            return Ok(None);
+        };
+
+        let mut bytes = vec![];
+        archive.by_name(&name)?.read_to_end(&mut bytes)?;
+        let reader = Cursor::new(bytes);
+        let ext = name.rsplit('.').next().unwrap_or_default().to_lowercase();
+        let df = match (ext.as_str(), DataFormat::from_extension(&ext)) {
+            (_, Some(DataFormat::Ipc)) => IpcReader::new(reader).finish()?,
+            (_, Some(DataFormat::Parquet)) => ParquetReader::new(reader).finish()?,
+            ("csv", _) => CsvReadOptions::default()
+                .with_has_header(true)
+                .map_parse_options(|options| options.with_try_parse_dates(true))
+                .into_reader_with_file_handle(reader)
+                .finish()?,
+            _ => return Err(anyhow!("不支持的文件格式: {name}")),
+        };
+        Ok(Some(df))
+    }
+}
+
+#[async_trait]
+impl DataProvider for LocalProvider {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    async fn fetch(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Time,
+        end: Time,
+    ) -> Result<DataFrame> {
+        let missing = || {
+            anyhow!(
+                "本地数据不存在 交易对: {code}, 周期: {interval}, 路径: {}",
+                self.path.display()
+            )
+        };
+        let tz = begin.timezone();
+
+        if self.archive {
+            let path = self.path.clone();
+            let df = tokio::task::spawn_blocking(move || {
+                Self::read_archive(&path, code, interval)?
+                    .map(|df| filter_range(normalize(df.lazy(), &tz)?, begin, end))
+                    .transpose()
+            })
+            .await??;
+            return df.ok_or_else(missing);
+        }
+
+        let path = self.find(code, interval).ok_or_else(missing)?;
+        tokio::task::spawn_blocking(move || {
+            filter_range(normalize(scan_file(&path)?, &tz)?, begin, end)
+        })
+        .await?
+    }
+}
@@ -0,0 +1,151 @@
+mod fixture;
+mod github;
+mod hyperliquid;
+mod local;
+
+use crate::{
+    data_store::CANDLE_COLUMNS,
+    types::{
+        alias::Time,
+        base::{Codes, DataSource, Interval},
+    },
+};
+use anyhow::{Result, bail, ensure};
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::{sync::Arc, time::Duration};
+
+pub use fixture::FixtureProvider;
+pub use github::GithubProvider;
+pub use hyperliquid::HyperliquidProvider;
+pub use local::LocalProvider;
+
+#[async_trait]
+pub trait DataProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn fetch(
+        &self,
+        code: Codes,
+        interval: Interval,
+        begin: Time,
+        end: Time,
+    ) -> Result<DataFrame>;
+}
+
+/// 超时、连接失败和服务端错误可以重试, 其余错误重试也不会成功
+pub fn retryable(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|err| err.downcast_ref::<reqwest::Error>())
+        .any(|err| {
+            err.is_timeout()
+                || err.is_connect()
+                || err.status().is_some_and(|status| status.is_server_error())
+        })
+}
+
+pub fn new_provider(
+    source: DataSource,
+    path: Option<&str>,
+    timeout: Duration,
+) -> Result<Arc<dyn DataProvider>> {
+    Ok(match source {
+        DataSource::Github => Arc::new(GithubProvider::new(timeout)?),
+        DataSource::Hyperliquid => Arc::new(HyperliquidProvider::new(timeout)?),
+        DataSource::Local => match path {
+            Some(path) => Arc::new(LocalProvider::new(path)?),
+            None => bail!("本地数据源必须指定路径"),
+        },
+        DataSource::Fixture => Arc::new(FixtureProvider),
+    })
+}
+
+pub fn normalize_time(df: LazyFrame, tz: &Tz) -> Result<LazyFrame> {
+    let mut df = df;
+    let schema = df.collect_schema()?;
+
+    if !schema.contains("time") {
+        match ["date", "datetime", "timestamp"]
+            .into_iter()
+            .find(|name| schema.contains(name))
+        {
+            Some(name) => df = df.rename([name], ["time"], true),
+            None => bail!("缺少时间列: {:?}", schema.iter_names().collect::<Vec<_>>()),
+        }
+    }
+
+    let schema = df.collect_schema()?;
+    let time = match schema.get("time") {
+        Some(DataType::Datetime(_, Some(_))) => col("time"),
+        Some(DataType::Datetime(_, None)) => col("time").dt().replace_time_zone(
+            Some(TimeZone::UTC),
+            lit("raise"),
+            NonExistent::Raise,
+        ),
+        Some(dtype) if dtype.is_integer() => col("time").cast(DataType::Datetime(
+            TimeUnit::Milliseconds,
+            Some(TimeZone::UTC),
+        )),
+        Some(dtype) => bail!("不支持的时间列类型: {dtype}"),
+        None => unreachable!(),
+    };
+
+    Ok(df.with_column(
+        time.dt()
+            .convert_time_zone(TimeZone::from_chrono(tz))
+            .dt()
+            .cast_time_unit(TimeUnit::Nanoseconds)
+            .alias("time"),
+    ))
+}
+
+/// 只保留请求的时间区间, 用于只能整体下载或读取的数据源
+pub fn filter_range(df: DataFrame, begin: Time, end: Time) -> Result<DataFrame> {
+    let time_type = DataType::Datetime(
+        TimeUnit::Nanoseconds,
+        Some(TimeZone::from_chrono(&begin.timezone())),
+    );
+    let time_lit = |time: Time| lit(time.naive_utc()).cast(time_type.clone());
+    Ok(df
+        .lazy()
+        .filter(
+            col("time")
+                .gt_eq(time_lit(begin))
+                .and(col("time").lt_eq(time_lit(end))),
+        )
+        .collect()?)
+}
+
+pub fn normalize(df: LazyFrame, tz: &Tz) -> Result<DataFrame> {
+    let mut df = normalize_time(df, tz)?;
+    let schema = df.collect_schema()?;
+    for name in &CANDLE_COLUMNS[1..6] {
+        ensure!(schema.contains(name), "缺少数据列: {name}");
+    }
+
+    let finished = if schema.contains("finished") {
+        col("finished").cast(DataType::Boolean)
+    } else {
+        lit(true).alias("finished")
+    };
+
+    let mut df = df
+        .select([
+            col("time"),
+            col("open").cast(DataType::Float64),
+            col("high").cast(DataType::Float64),
+            col("low").cast(DataType::Float64),
+            col("close").cast(DataType::Float64),
+            col("volume").cast(DataType::Float64),
+            finished,
+        ])
+        .sort(["time"], Default::default())
+        .unique_stable_generic(Some(vec!["time"]), UniqueKeepStrategy::Last)
+        .collect()?;
+
+    if df.should_rechunk() {
+        df.rechunk_mut();
+    }
+
+    Ok(df)
+}
@@ -0,0 +1,381 @@
+use crate::{
+    attribution::Attribution,
+    backtest::Backtest,
+    helpers::constants::FMT_S,
+    metrics::{Equity, Metrics, Relative},
+    types::{base::Codes, order::Order},
+};
+use anyhow::Result;
+use chrono::Datelike;
+use chrono_tz::Tz;
+use pyo3::pymethods;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+const CHART_WIDTH: f64 = 1000.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_POINTS: usize = 2000;
+
+const STYLE: &str = "body{font-family:-apple-system,'Segoe UI',sans-serif;margin:24px;color:#222}\
+h1{font-size:22px}h2{font-size:17px;margin-top:32px;border-bottom:1px solid #ddd;padding-bottom:4px}\
+table{border-collapse:collapse;font-size:13px}th,td{border:1px solid #ddd;padding:4px 8px;text-align:right}\
+th{background:#f5f5f5}td.l,th.l{text-align:left}.chart{width:100%;height:240px;background:#fafafa}\
+.axis{font-size:12px;color:#666}.note{font-size:12px;color:#888}";
+
+#[pymethods]
+impl Backtest {
+    /// 生成HTML报告
+    #[pyo3(name = "report")]
+    fn py_report(&self, path: &str) -> Result<()> {
+        self.write_report(Path::new(path))
+    }
+}
+
+impl Backtest {
+    /// 写入单个静态HTML报告, 不引用外部资源, 相同的回测结果生成相同的文件
+    pub fn write_report(&self, path: &Path) -> Result<()> {
+        let tz = *self.context().tz();
+        let config = self.config().clone();
+        let equity = self.equity().clone();
+        let orders = self.orders();
+        let metrics = self.metrics();
+
+        let mut html = String::new();
+        write!(
+            html,
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>回测报告</title><style>{STYLE}</style></head><body>"
+        )?;
+        write!(
+            html,
+            "<h1>回测报告</h1><p class=\"note\">fuxi {} | {} - {} | {}</p>",
+            env!("CARGO_PKG_VERSION"),
+            escape(&config.begin),
+            escape(&config.end),
+            escape(&config.tz),
+        )?;
+
+        html.push_str("<h2>指标</h2>");
+        write_metrics(&mut html, &metrics)?;
+
+        html.push_str("<h2>权益曲线</h2>");
+        write_chart(&mut html, &equity, "#1f77b4", &tz)?;
+
+        if let Some(benchmark) = self.benchmark()? {
+            html.push_str("<h2>基准</h2>");
+            write_chart(&mut html, &benchmark, "#ff7f0e", &tz)?;
+            write_relative(&mut html, &Relative::compute(&equity, &benchmark, &tz))?;
+        }
+
+        html.push_str("<h2>回撤</h2>");
+        write_chart(&mut html, &drawdowns(&equity), "#d62728", &tz)?;
+
+        html.push_str("<h2>月度收益</h2>");
+        write_monthly(&mut html, &equity, &tz)?;
+
+        html.push_str("<h2>交易对盈亏</h2>");
+        self.write_symbols(&mut html)?;
+
+        html.push_str("<h2>成交</h2>");
+        write_trades(&mut html, &orders, &tz)?;
+
+        html.push_str("<h2>配置</h2>");
+        write!(
+            html,
+            "<table><tr><th class=\"l\">开始</th><td>{}</td></tr><tr><th class=\"l\">结束</th><td>{}</td></tr>\
+             <tr><th class=\"l\">时区</th><td>{}</td></tr><tr><th class=\"l\">历史数据大小</th><td>{}</td></tr>\
+             <tr><th class=\"l\">现货资金</th><td>{}</td></tr><tr><th class=\"l\">合约资金</th><td>{}</td></tr>\
+             <tr><th class=\"l\">缺失数据处理</th><td>{}</td></tr><tr><th class=\"l\">数据源</th><td>{}</td></tr>\
+             <tr><th class=\"l\">盘口撮合</th><td>{}</td></tr><tr><th class=\"l\">逐笔回放</th><td>{}</td></tr>\
+             <tr><th class=\"l\">跳过空闲分钟</th><td>{}</td></tr><tr><th class=\"l\">随机数种子</th><td>{}</td></tr><tr><th class=\"l\">基准</th><td>{}</td></tr></table>",
+            escape(&config.begin),
+            escape(&config.end),
+            escape(&config.tz),
+            config.history_size,
+            config.spot,
+            config.swap,
+            config.gap_policy,
+            config.data_source,
+            config.book_fills,
+            config.tick_replay,
+            config.skip_idle,
+            config.seed,
+            config
+                .benchmark
+                .iter()
+                .map(|code| code.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )?;
+        html.push_str("<table style=\"margin-top:12px\"><tr><th class=\"l\">交易对</th><th>吃单费率</th><th>挂单费率</th><th>杠杆倍数</th></tr>");
+        for (code, taker, maker, lever) in &config.symbols {
+            write!(
+                html,
+                "<tr><td class=\"l\">{code}</td><td>{taker}</td><td>{maker}</td><td>{lever}</td></tr>"
+            )?;
+        }
+        html.push_str("</table></body></html>");
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, html)?;
+        Ok(())
+    }
+
+    /// 每个交易对的已实现/未实现盈亏, 手续费和资金费
+    fn write_symbols(&self, html: &mut String) -> Result<()> {
+        let mut codes: BTreeMap<Codes, Attribution> = BTreeMap::new();
+        let mut remarks: BTreeMap<String, Attribution> = BTreeMap::new();
+        for ((code, _, remark), item) in self.attribution()? {
+            merge(codes.entry(code).or_default(), &item);
+            merge(
+                remarks
+                    .entry(remark.unwrap_or_else(|| "-".to_string()))
+                    .or_default(),
+                &item,
+            );
+        }
+
+        write_attribution(html, "交易对", &codes)?;
+        html.push_str("<h2>备注盈亏</h2>");
+        write_attribution(html, "备注", &remarks)?;
+        html.push_str("<p class=\"note\">开仓成交按先进先出匹配平仓, 盈亏归到开仓订单的备注; 回测未加载资金费率, 资金费为0</p>");
+        Ok(())
+    }
+}
+
+fn merge(total: &mut Attribution, item: &Attribution) {
+    total.realized += item.realized;
+    total.unrealized += item.unrealized;
+    total.fees += item.fees;
+    total.funding += item.funding;
+}
+
+fn write_attribution<K: std::fmt::Display>(
+    html: &mut String,
+    title: &str,
+    items: &BTreeMap<K, Attribution>,
+) -> Result<()> {
+    write!(
+        html,
+        "<table><tr><th class=\"l\">{title}</th><th>已实现盈亏</th><th>未实现盈亏</th><th>手续费</th><th>资金费</th><th>净盈亏</th></tr>"
+    )?;
+    for (key, item) in items {
+        write!(
+            html,
+            "<tr><td class=\"l\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&key.to_string()),
+            decimal(item.realized),
+            decimal(item.unrealized),
+            decimal(item.fees),
+            decimal(item.funding),
+            decimal(item.net()),
+        )?;
+    }
+    html.push_str("</table>");
+    Ok(())
+}
+
+fn write_metrics(html: &mut String, metrics: &Metrics) -> Result<()> {
+    write!(
+        html,
+        "<table><tr><th>最终权益</th><th>总收益率</th><th>年化收益率</th><th>年化波动率</th><th>夏普比率</th>\
+         <th>最大回撤</th><th>订单数</th><th>成交订单数</th><th>手续费</th></tr>\
+         <tr><td>{:.2}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.4}</td></tr></table>",
+        metrics.final_equity,
+        percent(metrics.total_return),
+        percent(metrics.annual_return),
+        percent(metrics.volatility),
+        metrics.sharpe,
+        percent(metrics.max_drawdown),
+        metrics.orders,
+        metrics.fills,
+        metrics.fees,
+    )?;
+    Ok(())
+}
+
+fn write_relative(html: &mut String, relative: &Relative) -> Result<()> {
+    write!(
+        html,
+        "<table style=\"margin-top:12px\"><tr><th>基准收益率</th><th>Alpha(年化)</th><th>Beta</th><th>相关系数</th>\
+         <th>跟踪误差</th><th>信息比率</th></tr>\
+         <tr><td>{}</td><td>{}</td><td>{:.4}</td><td>{:.4}</td><td>{}</td><td>{:.2}</td></tr></table>",
+        percent(relative.benchmark_return),
+        percent(relative.alpha),
+        relative.beta,
+        relative.correlation,
+        percent(relative.tracking_error),
+        relative.information_ratio,
+    )?;
+    Ok(())
+}
+
+/// 内联SVG折线图, 点数过多时按步长抽样
+fn write_chart(html: &mut String, points: &Equity, color: &str, tz: &Tz) -> Result<()> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        html.push_str("<p class=\"note\">没有数据</p>");
+        return Ok(());
+    };
+
+    let step = points.len().div_ceil(CHART_POINTS);
+    let sampled = points
+        .iter()
+        .step_by(step)
+        .chain(std::iter::once(last))
+        .collect::<Vec<_>>();
+    let (min, max) = sampled
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), (_, value)| {
+            (min.min(*value), max.max(*value))
+        });
+    let span = if max > min { max - min } else { 1.0 };
+    let duration = (last.0 - first.0).max(1) as f64;
+
+    let line = sampled
+        .iter()
+        .map(|(time, value)| {
+            format!(
+                "{:.1},{:.1}",
+                (time - first.0) as f64 / duration * CHART_WIDTH,
+                CHART_HEIGHT - (value - min) / span * CHART_HEIGHT,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    write!(
+        html,
+        "<svg class=\"chart\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" preserveAspectRatio=\"none\">\
+         <polyline points=\"{line}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\" vector-effect=\"non-scaling-stroke\"/></svg>\
+         <div class=\"axis\">{} - {} | 最低 {:.4} | 最高 {:.4}</div>",
+        time_str(first.0, tz),
+        time_str(last.0, tz),
+        min,
+        max,
+    )?;
+    Ok(())
+}
+
+/// 月度收益热力图, 每行一年, 每列一个月
+fn write_monthly(html: &mut String, equity: &Equity, tz: &Tz) -> Result<()> {
+    let Some((_, initial)) = equity.first() else {
+        html.push_str("<p class=\"note\">没有数据</p>");
+        return Ok(());
+    };
+
+    let mut closes: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+    for (time, value) in equity {
+        let time = crate::helpers::time::nanos_to_time(*time, tz);
+        closes.insert((time.year(), time.month()), *value);
+    }
+
+    let mut returns: BTreeMap<i32, [Option<f64>; 12]> = BTreeMap::new();
+    let mut prev = *initial;
+    for ((year, month), close) in closes {
+        let value = if prev != 0.0 { close / prev - 1.0 } else { 0.0 };
+        returns.entry(year).or_default()[month as usize - 1] = Some(value);
+        prev = close;
+    }
+
+    html.push_str("<table><tr><th class=\"l\">年</th>");
+    for month in 1..=12 {
+        write!(html, "<th>{month}月</th>")?;
+    }
+    html.push_str("</tr>");
+    for (year, months) in returns {
+        write!(html, "<tr><td class=\"l\">{year}</td>")?;
+        for value in months {
+            match value {
+                Some(value) => {
+                    // 收益为绿色, 亏损为红色, ±10%时颜色最深
+                    let alpha = (value.abs() / 0.1).min(1.0) * 0.8;
+                    let rgb = if value >= 0.0 {
+                        "44,160,44"
+                    } else {
+                        "214,39,40"
+                    };
+                    write!(
+                        html,
+                        "<td style=\"background:rgba({rgb},{alpha:.2})\">{}</td>",
+                        percent(value)
+                    )?;
+                }
+                None => html.push_str("<td></td>"),
+            }
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    Ok(())
+}
+
+/// 有成交的订单, 按最后成交时间排序
+fn write_trades(html: &mut String, orders: &[Order], tz: &Tz) -> Result<()> {
+    let mut orders = orders
+        .iter()
+        .filter(|order| *order.deal_size() > Decimal::ZERO)
+        .collect::<Vec<_>>();
+    orders.sort_by_key(|order| (*order.update_time(), order.id().clone()));
+
+    html.push_str("<table><tr><th class=\"l\">时间</th><th class=\"l\">交易对</th><th class=\"l\">方式</th>\
+                   <th class=\"l\">方向</th><th class=\"l\">买卖</th><th class=\"l\">状态</th><th>成交数量</th>\
+                   <th>成交均价</th><th>手续费</th><th class=\"l\">备注</th></tr>");
+    for order in orders {
+        write!(
+            html,
+            "<tr><td class=\"l\">{}</td><td class=\"l\">{}</td><td class=\"l\">{}</td><td class=\"l\">{}</td>\
+             <td class=\"l\">{}</td><td class=\"l\">{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"l\">{}</td></tr>",
+            order.update_time().format(FMT_S),
+            order.code(),
+            order.method(),
+            order.direction(),
+            order.side(),
+            order.status(),
+            order.deal_size(),
+            decimal(*order.deal_price()),
+            decimal(*order.deal_fee()),
+            escape(order.remark().as_deref().unwrap_or_default()),
+        )?;
+    }
+    html.push_str("</table>");
+    Ok(())
+}
+
+/// 回撤序列: 权益相对历史最高点的跌幅(负数)
+fn drawdowns(equity: &Equity) -> Vec<(i64, f64)> {
+    let mut peak = f64::MIN;
+    equity
+        .iter()
+        .map(|(time, value)| {
+            peak = peak.max(*value);
+            let drawdown = if peak > 0.0 { value / peak - 1.0 } else { 0.0 };
+            (*time, drawdown)
+        })
+        .collect()
+}
+
+#[inline]
+fn time_str(nanos: i64, tz: &Tz) -> String {
+    crate::helpers::time::nanos_to_time(nanos, tz)
+        .format(FMT_S)
+        .to_string()
+}
+
+#[inline]
+fn percent(value: f64) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+#[inline]
+fn decimal(value: Decimal) -> String {
+    format!("{:.4}", value.to_f64().unwrap_or_default())
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,386 @@
+use crate::{
+    backtest::Backtest,
+    helpers::constants::RESULTS_DIR_ENV,
+    types::{
+        alias::Time,
+        base::{Codes, LogLevel},
+        order::Order,
+    },
+};
+use anyhow::{Result, anyhow, ensure};
+use fuxi_macros::model;
+use polars::prelude::*;
+use pyo3::pymethods;
+use pyo3_polars::PyDataFrame;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    fs::File,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+const META_FILE: &str = "run.json";
+const CONFIG_FILE: &str = "config.json";
+const METRICS_FILE: &str = "metrics.ipc";
+const EQUITY_FILE: &str = "equity.ipc";
+const TRADES_FILE: &str = "trades.ipc";
+const ORDERS_FILE: &str = "orders.json";
+const LOGS_FILE: &str = "logs.jsonl";
+
+/// 回测结果目录, 每次回测一个以运行ID命名的子目录
+#[model(python)]
+pub struct ResultStore {
+    dir: PathBuf,
+}
+
+/// 运行摘要
+#[derive(Serialize, Deserialize)]
+pub struct RunMeta {
+    pub id: String,
+    #[serde(with = "crate::helpers::time::serde_time")]
+    pub created: Time,
+    pub version: String,
+    pub begin: String,
+    pub end: String,
+    pub symbols: Vec<Codes>,
+    pub seed: u64,
+}
+
+/// 已保存的回测结果
+#[model(python)]
+pub struct Run {
+    pub id: String,
+    pub created: Time,
+    pub version: String,
+    pub config: String,
+    pub orders: Vec<Order>,
+    pub logs: Vec<String>,
+    metrics: DataFrame,
+    equity: DataFrame,
+    trades: DataFrame,
+}
+
+#[pymethods]
+impl Run {
+    #[getter(metrics)]
+    fn py_metrics(&self) -> PyDataFrame {
+        PyDataFrame(self.metrics().clone())
+    }
+
+    #[getter(equity)]
+    fn py_equity(&self) -> PyDataFrame {
+        PyDataFrame(self.equity().clone())
+    }
+
+    #[getter(trades)]
+    fn py_trades(&self) -> PyDataFrame {
+        PyDataFrame(self.trades().clone())
+    }
+}
+
+impl ResultStore {
+    pub fn new(dir: Option<&str>) -> Result<Self> {
+        let dir = match dir {
+            Some(dir) => PathBuf::from(dir),
+            None => match std::env::var(RESULTS_DIR_ENV) {
+                Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+                _ => PathBuf::from("results"),
+            },
+        };
+        let dir = if dir.is_absolute() {
+            dir
+        } else {
+            std::env::current_dir()?.join(dir)
+        };
+        Ok(Self::from(ResultStoreData { dir }))
+    }
+
+    /// 已保存的运行ID, 按保存时间排序
+    pub fn ids(&self) -> Result<Vec<String>> {
+        let dir = self.dir().clone();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if let Some(id) = path.file_name().and_then(|name| name.to_str())
+                && is_id(id)
+                && path.join(META_FILE).is_file()
+            {
+                ids.push(id.to_string());
+            }
+        }
+        // 运行ID为UUIDv7, 字典序即时间顺序
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub fn load(&self, id: &str) -> Result<Run> {
+        let dir = self.run_dir(id)?;
+        let meta: RunMeta = read_json(&dir.join(META_FILE))?;
+        Ok(Run::from(RunData {
+            id: meta.id,
+            created: meta.created,
+            version: meta.version,
+            config: std::fs::read_to_string(dir.join(CONFIG_FILE))?,
+            orders: read_json(&dir.join(ORDERS_FILE))?,
+            logs: read_json_lines(&dir.join(LOGS_FILE))?,
+            metrics: read_ipc(&dir.join(METRICS_FILE))?,
+            equity: read_ipc(&dir.join(EQUITY_FILE))?,
+            trades: read_ipc(&dir.join(TRADES_FILE))?,
+        }))
+    }
+
+    fn run_dir(&self, id: &str) -> Result<PathBuf> {
+        ensure!(is_id(id), "无效的运行ID: {id}");
+        let dir = self.dir().join(id);
+        ensure!(dir.join(META_FILE).is_file(), "回测结果不存在: {id}");
+        Ok(dir)
+    }
+}
+
+#[pymethods]
+impl ResultStore {
+    #[new]
+    #[pyo3(signature = (dir=None))]
+    fn _new(dir: Option<&str>) -> Result<Self> {
+        Self::new(dir)
+    }
+
+    #[getter(dir)]
+    fn py_dir(&self) -> String {
+        self.dir().display().to_string()
+    }
+
+    /// 已保存的回测结果, 每次运行一行
+    #[pyo3(name = "list")]
+    fn py_list(&self) -> Result<PyDataFrame> {
+        let mut frames = Vec::new();
+        for id in self.ids()? {
+            let dir = self.dir().join(&id);
+            let meta: RunMeta = read_json(&dir.join(META_FILE))?;
+            let mut df = DataFrame::new(vec![
+                Column::new("id".into(), vec![meta.id]),
+                Column::new(
+                    "created".into(),
+                    vec![meta.created.timestamp_nanos_opt().unwrap_or_default()],
+                )
+                .cast(&utc_time_type())?,
+                Column::new("version".into(), vec![meta.version]),
+                Column::new("begin".into(), vec![meta.begin]),
+                Column::new("end".into(), vec![meta.end]),
+                Column::new(
+                    "symbols".into(),
+                    vec![
+                        meta.symbols
+                            .iter()
+                            .map(|code| code.to_string())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    ],
+                ),
+            ])?;
+            df.hstack_mut(read_ipc(&dir.join(METRICS_FILE))?.get_columns())?;
+            frames.push(df);
+        }
+        Ok(PyDataFrame(stack(frames)?))
+    }
+
+    /// 加载回测结果
+    #[pyo3(name = "load")]
+    fn py_load(&self, id: &str) -> Result<Run> {
+        self.load(id)
+    }
+
+    /// 比较回测结果, 返回(指标, 权益曲线)
+    fn compare(&self, ids: Vec<String>) -> Result<(PyDataFrame, PyDataFrame)> {
+        ensure!(!ids.is_empty(), "至少需要一个运行ID");
+        let mut metrics = Vec::new();
+        let mut equity = Vec::new();
+        for id in &ids {
+            let dir = self.run_dir(id)?;
+            for (frames, file) in [(&mut metrics, METRICS_FILE), (&mut equity, EQUITY_FILE)] {
+                let mut df = read_ipc(&dir.join(file))?;
+                df.insert_column(0, Column::new("id".into(), vec![id.clone(); df.height()]))?;
+                frames.push(df);
+            }
+        }
+        Ok((PyDataFrame(stack(metrics)?), PyDataFrame(stack(equity)?)))
+    }
+}
+
+#[pymethods]
+impl Backtest {
+    /// 运行ID, 保存回测结果后设置
+    #[getter(run_id)]
+    fn py_run_id(&self) -> Option<String> {
+        self.run_id().clone()
+    }
+
+    /// 保存回测结果, 返回运行ID
+    #[pyo3(name = "save_run")]
+    fn py_save_run(&self) -> Result<String> {
+        self.save_run()
+    }
+}
+
+impl Backtest {
+    /// 保存回测结果: 先写入临时目录再重命名, 列出结果时不会看到不完整的运行
+    pub fn save_run(&self) -> Result<String> {
+        let results = self.results().clone();
+        let id = crate::helpers::id::new();
+        let dir = results.dir().join(&id);
+        let tmp_dir = results.dir().join(format!(".{id}.tmp"));
+        std::fs::create_dir_all(&tmp_dir)?;
+
+        let config = self.config().clone();
+        let meta = RunMeta {
+            id: id.clone(),
+            created: crate::helpers::time::now(&self.context().tz()),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            begin: config.begin.clone(),
+            end: config.end.clone(),
+            symbols: config.symbols.iter().map(|(code, ..)| *code).collect(),
+            seed: config.seed,
+        };
+        let mut orders = self.orders();
+        orders.sort_by_key(|order| (*order.create_time(), order.id().clone()));
+
+        write_json(&tmp_dir.join(CONFIG_FILE), &config)?;
+        write_json(&tmp_dir.join(ORDERS_FILE), &orders)?;
+        match self.context().logs_mut().as_mut() {
+            Some(logs) => logs.copy_to(&tmp_dir.join(LOGS_FILE))?,
+            None => File::create(tmp_dir.join(LOGS_FILE))?.sync_all()?,
+        }
+        write_ipc(&tmp_dir.join(METRICS_FILE), &mut self.metrics_frame()?)?;
+        write_ipc(&tmp_dir.join(EQUITY_FILE), &mut self.equity_frame()?)?;
+        write_ipc(&tmp_dir.join(TRADES_FILE), &mut self.trades_frame(&orders)?)?;
+        write_json(&tmp_dir.join(META_FILE), &meta)?;
+        std::fs::rename(&tmp_dir, &dir)?;
+
+        self.set_run_id(Some(id.clone()));
+        self.context().show_log(
+            LogLevel::Info,
+            format_args!("保存回测结果 运行ID: {id} 路径: {}", dir.display()),
+        );
+        Ok(id)
+    }
+
+    /// 成交列表: 有成交的订单按更新时间排序
+    fn trades_frame(&self, orders: &[Order]) -> Result<DataFrame> {
+        let mut orders = orders
+            .iter()
+            .filter(|order| *order.deal_size() > Decimal::ZERO)
+            .collect::<Vec<_>>();
+        orders.sort_by_key(|order| (*order.update_time(), order.id().clone()));
+
+        let strings = |get: fn(&Order) -> String| orders.iter().map(|o| get(o)).collect::<Vec<_>>();
+        let f64s = |get: fn(&Order) -> Decimal| {
+            orders
+                .iter()
+                .map(|o| get(o).to_f64().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        Ok(DataFrame::new(vec![
+            Column::new(
+                "time".into(),
+                orders
+                    .iter()
+                    .map(|o| o.update_time().timestamp_nanos_opt().unwrap_or_default())
+                    .collect::<Vec<_>>(),
+            )
+            .cast(&self.data_store().time_type())?,
+            Column::new("id".into(), strings(|o| o.id().clone())),
+            Column::new("code".into(), strings(|o| o.code().to_string())),
+            Column::new("method".into(), strings(|o| o.method().to_string())),
+            Column::new("direction".into(), strings(|o| o.direction().to_string())),
+            Column::new("side".into(), strings(|o| o.side().to_string())),
+            Column::new("size".into(), f64s(|o| *o.deal_size())),
+            Column::new("price".into(), f64s(|o| *o.deal_price())),
+            Column::new("fee".into(), f64s(|o| *o.deal_fee())),
+            Column::new(
+                "remark".into(),
+                orders
+                    .iter()
+                    .map(|o| o.remark().clone())
+                    .collect::<Vec<_>>(),
+            ),
+        ])?)
+    }
+}
+
+/// 运行ID只包含字母和数字, 保存中的临时目录不是运行ID
+#[inline]
+fn is_id(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn utc_time_type() -> DataType {
+    DataType::Datetime(
+        TimeUnit::Nanoseconds,
+        Some(TimeZone::from_chrono(&chrono_tz::UTC)),
+    )
+}
+
+/// 纵向合并, 缺少的列补空值, 同名列按第一次出现时的类型转换
+fn stack(frames: Vec<DataFrame>) -> Result<DataFrame> {
+    let mut schema = Schema::default();
+    for df in &frames {
+        for column in df.get_columns() {
+            if !schema.contains(column.name()) {
+                schema.with_column(column.name().clone(), column.dtype().clone());
+            }
+        }
+    }
+
+    let mut result = DataFrame::empty_with_schema(&schema);
+    for df in frames {
+        let columns = schema
+            .iter()
+            .map(|(name, dtype)| match df.column(name) {
+                Ok(column) => column.cast(dtype),
+                Err(_) => Ok(Column::full_null(name.clone(), df.height(), dtype)),
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        result.vstack_mut(&DataFrame::new(columns)?)?;
+    }
+    Ok(result)
+}
+
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(&serde_json::to_vec(value)?)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path).map_err(|err| anyhow!("读取回测结果失败: {path:?} {err}"))?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+fn read_json_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let file = File::open(path).map_err(|err| anyhow!("读取回测结果失败: {path:?} {err}"))?;
+    let mut items = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if !line.is_empty() {
+            items.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(items)
+}
+
+fn write_ipc(path: &Path, df: &mut DataFrame) -> Result<()> {
+    let mut file = File::create(path)?;
+    IpcWriter::new(&mut file).finish(df)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn read_ipc(path: &Path) -> Result<DataFrame> {
+    let file = File::open(path).map_err(|err| anyhow!("读取回测结果失败: {path:?} {err}"))?;
+    Ok(IpcReader::new(file).finish()?)
+}
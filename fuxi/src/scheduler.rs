@@ -0,0 +1,309 @@
+use crate::types::{alias::Time, base::Timer};
+use anyhow::{Result, ensure};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use chrono_tz::Tz;
+use fuxi_macros::model;
+
+#[derive(Clone)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields = expr.split_whitespace().collect::<Vec<_>>();
+        ensure!(
+            fields.len() == 5,
+            "无效cron表达式, 需要5个字段(分 时 日 月 周): {expr}"
+        );
+
+        let mut weekdays = Self::parse_field(fields[4], 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+
+        Ok(Self {
+            minutes: Self::parse_field(fields[0], 0, 59)?,
+            hours: Self::parse_field(fields[1], 0, 23)?,
+            days: Self::parse_field(fields[2], 1, 31)?,
+            months: Self::parse_field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>()?),
+                None => (part, 1),
+            };
+            ensure!(step > 0, "无效cron步长: {field}");
+
+            let (begin, end) = if range == "*" {
+                (min, max)
+            } else if let Some((begin, end)) = range.split_once('-') {
+                (begin.parse::<u32>()?, end.parse::<u32>()?)
+            } else {
+                let value = range.parse::<u32>()?;
+                (value, if part.contains('/') { max } else { value })
+            };
+            ensure!(
+                min <= begin && begin <= end && end <= max,
+                "cron字段超出范围[{min}-{max}]: {field}"
+            );
+
+            for value in (begin..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(bits)
+    }
+
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        let hit = |bits: u64, value: u32| bits & (1 << value) != 0;
+
+        let day = hit(self.days, time.day());
+        let weekday = hit(self.weekdays, time.weekday().num_days_from_sunday());
+        let day = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+
+        day && hit(self.minutes, time.minute())
+            && hit(self.hours, time.hour())
+            && hit(self.months, time.month())
+    }
+}
+
+#[derive(Clone)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(Cron),
+}
+
+impl Schedule {
+    /// 固定周期: 数字+单位, 周期为0时永远不会触发, 直接拒绝
+    pub fn every(value: &str) -> Result<Self> {
+        let period = crate::helpers::time::parse_duration(value)?;
+        ensure!(period > Duration::zero(), "无效定时器周期: {value}");
+        Ok(Self::Every(period))
+    }
+}
+
+#[derive(Clone)]
+pub struct TimerSpec {
+    pub name: String,
+    pub schedule: Schedule,
+    pub offset: Duration,
+    pub tz: Option<Tz>,
+}
+
+impl TimerSpec {
+    pub fn is_due(&self, time: &Time) -> bool {
+        let local = match &self.tz {
+            Some(tz) => time.with_timezone(tz).naive_local(),
+            None => time.naive_local(),
+        } - self.offset;
+
+        match &self.schedule {
+            Schedule::Every(period) => {
+                // 以1970-01-05(周一) 00:00为对齐基准, 按周的周期落在周一
+                let anchor = NaiveDate::from_ymd_opt(1970, 1, 5)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap();
+                let elapsed = (local - anchor).num_seconds();
+                let period = period.num_seconds();
+                local.second() == 0 && period > 0 && elapsed.rem_euclid(period) == 0
+            }
+            Schedule::Cron(cron) => local.second() == 0 && cron.matches(&local),
+        }
+    }
+}
+
+#[model]
+pub struct Scheduler {
+    timers: Vec<TimerSpec>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        let builtin = |timer: Timer, period: Duration| TimerSpec {
+            name: timer.to_string(),
+            schedule: Schedule::Every(period),
+            offset: Duration::zero(),
+            tz: None,
+        };
+        Self::from(SchedulerData {
+            timers: vec![
+                builtin(Timer::Daily, Duration::days(1)),
+                builtin(Timer::Hourly, Duration::hours(1)),
+                builtin(Timer::Minutely, Duration::minutes(1)),
+            ],
+        })
+    }
+}
+
+impl Scheduler {
+    pub fn add(&self, spec: TimerSpec) -> Result<()> {
+        let mut timers = self.timers_mut();
+        ensure!(
+            !timers.iter().any(|timer| timer.name == spec.name),
+            "重复定时器: {}",
+            spec.name
+        );
+        timers.push(spec);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.timers().iter().any(|timer| timer.name == name)
+    }
+
+    pub fn remove(&self, name: &str) -> bool {
+        let mut timers = self.timers_mut();
+        let len = timers.len();
+        timers.retain(|timer| timer.name != name);
+        timers.len() != len
+    }
+
+    pub fn due(&self, time: &Time) -> Vec<String> {
+        self.timers()
+            .iter()
+            .filter(|timer| timer.is_due(time))
+            .map(|timer| timer.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::{Asia::Shanghai, UTC};
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn spec(name: &str, schedule: Schedule) -> TimerSpec {
+        TimerSpec {
+            name: name.to_owned(),
+            schedule,
+            offset: Duration::zero(),
+            tz: None,
+        }
+    }
+
+    #[test]
+    fn cron_parse_errors() {
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-3 * * * *",
+            "a * * * *",
+        ] {
+            assert!(Cron::parse(expr).is_err(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn cron_matches() {
+        // 2024-01-01是周一
+        let cron = Cron::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(cron.matches(&at(2024, 1, 1, 9, 0)));
+        assert!(cron.matches(&at(2024, 1, 1, 17, 45)));
+        assert!(!cron.matches(&at(2024, 1, 1, 9, 10)));
+        assert!(!cron.matches(&at(2024, 1, 1, 18, 0)));
+        assert!(!cron.matches(&at(2024, 1, 6, 9, 0)));
+
+        // 周字段的7等同于0(周日)
+        let cron = Cron::parse("0 0 * * 7").unwrap();
+        assert!(cron.matches(&at(2024, 1, 7, 0, 0)));
+        assert!(!cron.matches(&at(2024, 1, 8, 0, 0)));
+
+        // 日和周都限定时任一命中即可
+        let cron = Cron::parse("30 8 15 * 1").unwrap();
+        assert!(cron.matches(&at(2024, 1, 15, 8, 30)));
+        assert!(cron.matches(&at(2024, 1, 8, 8, 30)));
+        assert!(!cron.matches(&at(2024, 1, 9, 8, 30)));
+
+        let cron = Cron::parse("0 0 1 1,7 *").unwrap();
+        assert!(cron.matches(&at(2024, 7, 1, 0, 0)));
+        assert!(!cron.matches(&at(2024, 2, 1, 0, 0)));
+    }
+
+    #[test]
+    fn every_rejects_empty_period() {
+        assert!(Schedule::every("0m").is_err());
+        assert!(Schedule::every("0d").is_err());
+        assert!(Schedule::every("m").is_err());
+        assert!(matches!(
+            Schedule::every("5m").unwrap(),
+            Schedule::Every(period) if period == Duration::minutes(5)
+        ));
+    }
+
+    #[test]
+    fn timer_is_due() {
+        let weekly = spec("weekly", Schedule::Every(Duration::weeks(1)));
+        assert!(weekly.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        assert!(!weekly.is_due(&UTC.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()));
+
+        let hourly = spec("hourly", Schedule::Every(Duration::hours(1)));
+        assert!(hourly.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 3, 0, 0).unwrap()));
+        assert!(!hourly.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 3, 0, 1).unwrap()));
+        assert!(!hourly.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 3, 1, 0).unwrap()));
+
+        // 偏移和时区作用于本地时间
+        let daily = TimerSpec {
+            offset: Duration::hours(8),
+            tz: Some(Shanghai),
+            ..spec("daily", Schedule::Every(Duration::days(1)))
+        };
+        assert!(daily.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        assert!(!daily.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap()));
+
+        let cron = TimerSpec {
+            tz: Some(Shanghai),
+            ..spec("cron", Schedule::Cron(Cron::parse("0 9 * * *").unwrap()))
+        };
+        assert!(cron.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap()));
+        assert!(!cron.is_due(&UTC.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn scheduler_due_and_remove() {
+        let scheduler = Scheduler::default();
+        let time = UTC.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(scheduler.due(&time).len(), 3);
+
+        let cron = spec("open", Schedule::Cron(Cron::parse("0 0 * * *").unwrap()));
+        scheduler.add(cron.clone()).unwrap();
+        assert!(scheduler.add(cron).is_err());
+        assert!(scheduler.due(&time).contains(&"open".to_owned()));
+
+        assert!(scheduler.remove(&Timer::Minutely.to_string()));
+        assert!(!scheduler.remove(&Timer::Minutely.to_string()));
+        let time = UTC.with_ymd_and_hms(2024, 1, 1, 0, 1, 0).unwrap();
+        assert!(scheduler.due(&time).is_empty());
+    }
+}
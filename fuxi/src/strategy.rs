@@ -1,14 +1,28 @@
 use crate::{
     backtest::Backtest,
     context::Context,
-    types::base::{Codes, Timer},
+    types::{base::Codes, market::Trade},
 };
 use anyhow::Result;
 use pyo3::{Bound, Py, PyAny, Python, types::PyAnyMethods};
 use pyo3_polars::PyDataFrame;
-use std::sync::Arc;
+use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+/// 策略可订阅的事件, 对应策略中重写的on_xxx方法
+#[derive(Clone, Copy, PartialEq, Eq, Hash, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Event {
+    Candle,
+    Signal,
+    Trade,
+    Timer,
+    Position,
+    Order,
+    Cash,
+}
 
 pub struct Strategy {
+    events: HashSet<Event>,
     on_inject_context: Py<PyAny>,
     on_inject_backtest: Py<PyAny>,
     on_init: Py<PyAny>,
@@ -16,6 +30,7 @@ pub struct Strategy {
     on_history_candle: Py<PyAny>,
     on_candle: Py<PyAny>,
     on_backtest_tick: Py<PyAny>,
+    on_trade: Py<PyAny>,
     on_timer: Py<PyAny>,
     on_position: Py<PyAny>,
     on_order: Py<PyAny>,
@@ -31,12 +46,20 @@ impl Strategy {
         let on_history_candle = instance.getattr("_on_history_candle")?.unbind();
         let on_candle = instance.getattr("_on_candle")?.unbind();
         let on_backtest_tick = instance.getattr("_on_backtest_tick")?.unbind();
+        let on_trade = instance.getattr("_on_trade")?.unbind();
         let on_timer = instance.getattr("_on_timer")?.unbind();
         let on_position = instance.getattr("_on_position")?.unbind();
         let on_order = instance.getattr("_on_order")?.unbind();
         let on_cash = instance.getattr("_on_cash")?.unbind();
+        let events = instance
+            .call_method0("_subscriptions")?
+            .extract::<Vec<String>>()?
+            .iter()
+            .filter_map(|name| Event::from_str(name).ok())
+            .collect();
 
         Ok(Arc::new(Self {
+            events,
             on_inject_context,
             on_inject_backtest,
             on_init,
@@ -44,6 +67,7 @@ impl Strategy {
             on_history_candle,
             on_candle,
             on_backtest_tick,
+            on_trade,
             on_timer,
             on_position,
             on_order,
@@ -51,6 +75,11 @@ impl Strategy {
         }))
     }
 
+    #[inline]
+    pub fn subscribes(&self, event: Event) -> bool {
+        self.events.contains(&event)
+    }
+
     #[inline]
     pub fn on_inject_context(&self, context: Context) -> Result<()> {
         Python::with_gil(|py| self.on_inject_context.call1(py, (context,)))?;
@@ -81,8 +110,8 @@ impl Strategy {
         Ok(())
     }
     #[inline]
-    pub fn on_candle(&self, code: Codes, candles: Option<PyDataFrame>) -> Result<()> {
-        Python::with_gil(|py| self.on_candle.call1(py, (code, candles)))?;
+    pub fn on_candle(&self, code: Codes, candles: Option<PyDataFrame>, notify: bool) -> Result<()> {
+        Python::with_gil(|py| self.on_candle.call1(py, (code, candles, notify)))?;
         Ok(())
     }
 
@@ -93,25 +122,46 @@ impl Strategy {
     }
 
     #[inline]
-    pub fn on_timer(&self, timer: Timer) -> Result<()> {
-        Python::with_gil(|py| self.on_timer.call1(py, (timer,)))?;
+    pub fn on_trade(&self, trade: Trade) -> Result<()> {
+        if !self.subscribes(Event::Trade) {
+            return Ok(());
+        }
+        Python::with_gil(|py| self.on_trade.call1(py, (trade,)))?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn on_timer(&self, name: &str) -> Result<()> {
+        if !self.subscribes(Event::Timer) {
+            return Ok(());
+        }
+        Python::with_gil(|py| self.on_timer.call1(py, (name,)))?;
         Ok(())
     }
 
     #[inline]
     pub fn on_position(&self) -> Result<()> {
+        if !self.subscribes(Event::Position) {
+            return Ok(());
+        }
         Python::with_gil(|py| self.on_position.call0(py))?;
         Ok(())
     }
 
     #[inline]
     pub fn on_order(&self) -> Result<()> {
+        if !self.subscribes(Event::Order) {
+            return Ok(());
+        }
         Python::with_gil(|py| self.on_order.call0(py))?;
         Ok(())
     }
 
     #[inline]
     pub fn on_cash(&self) -> Result<()> {
+        if !self.subscribes(Event::Cash) {
+            return Ok(());
+        }
         Python::with_gil(|py| self.on_cash.call0(py))?;
         Ok(())
     }
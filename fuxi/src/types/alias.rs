@@ -1,4 +1,4 @@
-use crate::helpers::constants::OFFSET;
+use crate::helpers::constants::DEFAULT_TZ;
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
 use indexmap::IndexMap;
@@ -14,7 +14,7 @@ pub type Time = DateTime<Tz>;
 
 #[inline]
 pub fn default_time() -> Time {
-    DateTime::<Utc>::default().with_timezone(&OFFSET)
+    DateTime::<Utc>::default().with_timezone(&DEFAULT_TZ)
 }
 
 pub type Safe<T> = Arc<RwLock<T>>;
@@ -70,6 +70,51 @@ pub enum Interval {
     Month,
 }
 
+impl Interval {
+    pub fn abbr(&self) -> &'static str {
+        match self {
+            Self::Min => "1m",
+            Self::Min3 => "3m",
+            Self::Min5 => "5m",
+            Self::Min15 => "15m",
+            Self::Min30 => "30m",
+            Self::Hour => "1h",
+            Self::Hour2 => "2h",
+            Self::Hour4 => "4h",
+            Self::Hour8 => "8h",
+            Self::Hour12 => "12h",
+            Self::Day => "1d",
+            Self::Day3 => "3d",
+            Self::Week => "1w",
+            Self::Month => "1mo",
+        }
+    }
+
+    pub fn minutes(&self) -> Option<i64> {
+        match self {
+            Self::Min => Some(1),
+            Self::Min3 => Some(3),
+            Self::Min5 => Some(5),
+            Self::Min15 => Some(15),
+            Self::Min30 => Some(30),
+            Self::Hour => Some(60),
+            Self::Hour2 => Some(2 * 60),
+            Self::Hour4 => Some(4 * 60),
+            Self::Hour8 => Some(8 * 60),
+            Self::Hour12 => Some(12 * 60),
+            Self::Day => Some(24 * 60),
+            Self::Day3 => Some(3 * 24 * 60),
+            Self::Week => Some(7 * 24 * 60),
+            Self::Month => None,
+        }
+    }
+
+    pub fn from_abbr(value: &str) -> Option<Self> {
+        use strum::IntoEnumIterator;
+        Self::iter().find(|interval| interval.abbr() == value)
+    }
+}
+
 #[model(python)]
 pub enum Timer {
     Daily,
@@ -77,9 +122,54 @@ pub enum Timer {
     Minutely,
 }
 
-define_coins_with_codes!();
+#[model(python)]
+pub enum GapPolicy {
+    Fill,
+    Null,
+    Missing,
+}
+
+#[model(python)]
+pub enum Resample {
+    Shuffle,
+    Bootstrap,
+    Perturb,
+}
 
 #[model(python)]
+pub enum DataSource {
+    Github,
+    Local,
+    Hyperliquid,
+    Fixture,
+}
+
+#[model(python)]
+pub enum DataFormat {
+    Ipc,
+    Parquet,
+}
+
+impl DataFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ipc => "feather",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    pub fn from_extension(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "feather" | "arrow" | "ipc" => Some(Self::Ipc),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+define_coins_with_codes!();
+
+#[model(python, serde)]
 pub struct Volume {
     pub total: Size,
     pub avail: Size,
@@ -97,7 +187,7 @@ impl Default for Volume {
     }
 }
 
-#[model(python)]
+#[model(python, serde)]
 pub struct Pnl {
     pub realized: Size,
     pub unrealized: Size,
@@ -1,10 +1,11 @@
 use crate::types::{
     alias::{Size, Time, default_time},
-    base::Codes,
+    base::{Codes, Side},
     position::Position,
 };
 use fuxi_macros::{define_map, model};
 use polars::frame::DataFrame;
+use pyo3::pymethods;
 
 #[model(python)]
 pub struct Candle {
@@ -43,12 +44,67 @@ impl FundingRate {
     }
 }
 
+#[model(python)]
+pub struct OrderBook {
+    pub code: Codes,
+    pub time: Time,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+impl OrderBook {
+    pub fn new(code: Codes, time: Time) -> Self {
+        OrderBookData {
+            code,
+            time,
+            bids: Default::default(),
+            asks: Default::default(),
+        }
+        .into()
+    }
+
+    /// 指定价格档位的挂单量, 买盘为true
+    pub fn level_size(&self, is_bid: bool, price: f64) -> f64 {
+        let levels = if is_bid { self.bids() } else { self.asks() };
+        levels
+            .iter()
+            .find(|(level, _)| *level == price)
+            .map(|(_, size)| *size)
+            .unwrap_or_default()
+    }
+}
+
+#[pymethods]
+impl OrderBook {
+    fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids().first().copied()
+    }
+
+    fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks().first().copied()
+    }
+
+    fn mid(&self) -> Option<f64> {
+        Some((self.best_bid()?.0 + self.best_ask()?.0) / 2.0)
+    }
+}
+
+#[model(python)]
+pub struct Trade {
+    pub code: Codes,
+    pub time: Time,
+    pub side: Side,
+    pub price: f64,
+    pub size: f64,
+}
+
 #[model(python)]
 pub struct Symbol {
     pub code: Codes,
     pub taker: Size,
     pub maker: Size,
     pub position: Position,
+    pub book: Option<OrderBook>,
     candles: DataFrame,
 }
 
@@ -61,6 +117,7 @@ impl Symbol {
             taker,
             maker,
             position: Position::new(code, lever),
+            book: None,
             candles: Default::default(),
         })
     }
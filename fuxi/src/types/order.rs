@@ -4,7 +4,7 @@ use crate::types::{
 };
 use fuxi_macros::{define_map, model};
 
-#[model(python)]
+#[model(python, serde)]
 pub struct Order {
     pub code: Codes,
     pub id: String,
@@ -19,11 +19,24 @@ pub struct Order {
     pub deal_fee: Size,
     pub margin: Size,
     pub remark: Option<String>,
+    #[serde(with = "crate::helpers::time::serde_time")]
     pub create_time: Time,
+    #[serde(with = "crate::helpers::time::serde_time")]
     pub update_time: Time,
 }
 
-define_map!(pub OrderMap is String to Order);
+impl Order {
+    /// 开仓订单: 做多买入或做空卖出
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        matches!(
+            (*self.direction(), *self.side()),
+            (Direction::Long, Side::Buy) | (Direction::Short, Side::Sell)
+        )
+    }
+}
+
+define_map!(pub OrderMap is String to Order, serde);
 
 impl OrderMap {
     #[inline]
@@ -5,7 +5,7 @@ use crate::types::{
 };
 use fuxi_macros::model;
 
-#[model(python)]
+#[model(python, serde)]
 pub struct SidePosition {
     pub code: Codes,
     pub direction: Direction,
@@ -27,7 +27,7 @@ impl SidePosition {
     }
 }
 
-#[model(python)]
+#[model(python, serde)]
 pub struct Position {
     pub code: Codes,
     pub margin: Volume,
@@ -51,4 +51,13 @@ impl Position {
         }
         .into()
     }
+
+    /// 指定方向的持仓
+    #[inline]
+    pub fn side(&self, direction: Direction) -> SidePosition {
+        match direction {
+            Direction::Long => self.long().clone(),
+            Direction::Short => self.short().clone(),
+        }
+    }
 }